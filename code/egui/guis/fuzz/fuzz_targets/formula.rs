@@ -0,0 +1,55 @@
+//! Parses arbitrary input as a formula, checks that printing a valid formula and parsing it
+//! again gives the same expression and evaluates it in a sheet.
+//!
+//! Run it with `cargo fuzz run formula` in the `guis` directory.
+
+#![no_main]
+
+use guis::cells::{
+    formula::{self, Expr, ExprKind},
+    Coord, Sheet,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let input = match std::str::from_utf8(data) {
+        Ok(input) => input,
+        Err(_) => return,
+    };
+
+    let formula = formula::parse(input);
+    if formula.is_valid() {
+        let printed = format!("={}", formula.expr);
+        let reparsed = formula::parse(&printed);
+        assert!(reparsed.is_valid(), "{} printed as {}", input, printed);
+        assert_eq!(
+            without_spans(reparsed.expr),
+            without_spans(formula.expr),
+            "{} printed as {}",
+            input,
+            printed
+        );
+    }
+
+    formula::adjust_references(input, |reference| reference.offset(1, 1));
+
+    let mut sheet = Sheet::new();
+    sheet.set(Coord { column: 0, row: 0 }, input);
+    sheet.set(Coord { column: 1, row: 0 }, "=A0 + 1");
+    sheet.value(Coord { column: 1, row: 0 });
+});
+
+/// The expression with all spans removed, which differ between equal expressions written
+/// differently.
+fn without_spans(expr: Expr) -> Expr {
+    let strip = |expr: Box<Expr>| Box::new(without_spans(*expr));
+    let kind = match expr.kind {
+        ExprKind::Unary(op, operand) => ExprKind::Unary(op, strip(operand)),
+        ExprKind::Binary(op, lhs, rhs) => ExprKind::Binary(op, strip(lhs), strip(rhs)),
+        ExprKind::Call(name, args) => {
+            ExprKind::Call(name, args.into_iter().map(without_spans).collect())
+        }
+        kind => kind,
+    };
+    Expr { kind, span: 0..0 }
+}
@@ -0,0 +1,86 @@
+//! Evaluates a Cells document or a CSV file with formulas without opening a window, e.g. to
+//! check spreadsheets in CI:
+//!
+//! ```text
+//! cells-eval <file> [cell]
+//! ```
+//!
+//! It doesn't need egui, so `cargo run --no-default-features --bin cells-eval -- <file>` builds
+//! it without the user interface.
+//!
+//! Prints the values of all cells as CSV, or only the value of the given cell. Files ending in
+//! `.csv` are imported with their first field in `A0`, everything else is read as a document.
+//!
+//! The exit code is 1 if any printed cell evaluates to an error, which are listed on stderr,
+//! and 2 if the arguments are invalid or the file cannot be read.
+
+use std::{error::Error, path::Path, process};
+
+use guis::cells::{document::Document, Coord, CsvExport, Sheet};
+
+const USAGE: &str = "usage: cells-eval <file> [cell]";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (path, cell) = match args.as_slice() {
+        [path] => (path, None),
+        [path, cell] => (path, Some(cell)),
+        _ => fail(USAGE),
+    };
+    let cell = cell.map(|cell| {
+        cell.parse::<Coord>()
+            .unwrap_or_else(|()| fail(&format!("`{}` is not a cell", cell)))
+    });
+    let sheet = load(Path::new(path))
+        .unwrap_or_else(|error| fail(&format!("cannot read {}: {}", path, error)));
+
+    let checked = match cell {
+        Some(coord) => {
+            println!("{}", sheet.value(coord));
+            vec![coord]
+        }
+        None => {
+            print!("{}", sheet.export_csv(CsvExport::Values));
+            sheet.used_coords()
+        }
+    };
+
+    let mut has_errors = false;
+    for coord in checked {
+        let value = sheet.value(coord);
+        if !value.is_error() {
+            continue;
+        }
+        has_errors = true;
+        if let Some(error) = sheet.errors(coord).first() {
+            eprintln!("{}: {} ({})", coord, value, error);
+        } else if let Some(cycle) = sheet.cycle(coord) {
+            let path: Vec<String> = cycle.iter().map(Coord::to_string).collect();
+            eprintln!("{}: {} ({})", coord, value, path.join(" → "));
+        } else {
+            eprintln!("{}: {}", coord, value);
+        }
+    }
+    if has_errors {
+        process::exit(1);
+    }
+}
+
+fn load(path: &Path) -> Result<Sheet, Box<dyn Error>> {
+    let input = std::fs::read_to_string(path)?;
+    let mut sheet = Sheet::new();
+    let is_csv = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    if is_csv {
+        sheet.import_csv(&input, Coord { column: 0, row: 0 })?;
+    } else {
+        sheet.load_document(Document::from_json(&input)?);
+    }
+    Ok(sheet)
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("cells-eval: {}", message);
+    process::exit(2);
+}
@@ -0,0 +1,19 @@
+pub mod csv;
+pub mod document;
+pub mod filter;
+pub mod format;
+pub mod formula;
+pub mod functions;
+pub mod graph;
+pub mod history;
+pub mod names;
+pub mod sheet;
+pub mod sort;
+pub mod structure;
+#[cfg(feature = "gui")]
+mod ui;
+pub mod value;
+
+pub use sheet::{Area, Coord, CsvExport, Fill, Sheet, COLUMNS, ROWS};
+#[cfg(feature = "gui")]
+pub use ui::{cells, CellsModel, Selection};
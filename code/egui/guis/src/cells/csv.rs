@@ -0,0 +1,110 @@
+//! Reading and writing comma separated values as described in RFC 4180. Fields containing
+//! commas, quotes or line breaks are enclosed in quotes, and quotes inside of them are doubled.
+
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvError {
+    /// Line of the input on which the error occurred, starting with 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+/// Splits the input into records of fields. A line break at the end of the input does not start
+/// another record.
+pub fn parse(input: &str) -> Result<Vec<Vec<String>>, CsvError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() => {
+                let start_line = line;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            field.push(c);
+                        }
+                        None => {
+                            return Err(CsvError {
+                                line: start_line,
+                                message: "quoted field is not closed".to_string(),
+                            })
+                        }
+                    }
+                }
+                match chars.peek() {
+                    None | Some(',' | '\r' | '\n') => {}
+                    Some(c) => {
+                        return Err(CsvError {
+                            line,
+                            message: format!("unexpected `{}` after quoted field", c),
+                        })
+                    }
+                }
+            }
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+                line += 1;
+            }
+            c => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Joins records into lines of comma separated fields.
+pub fn write<R, F>(records: impl IntoIterator<Item = R>) -> String
+where
+    R: IntoIterator<Item = F>,
+    F: AsRef<str>,
+{
+    let mut output = String::new();
+    for record in records {
+        for (index, field) in record.into_iter().enumerate() {
+            if index > 0 {
+                output.push(',');
+            }
+            write_field(&mut output, field.as_ref());
+        }
+        output.push('\n');
+    }
+    output
+}
+
+fn write_field(output: &mut String, field: &str) {
+    if field.contains(&[',', '"', '\r', '\n'][..]) {
+        output.push('"');
+        output.push_str(&field.replace('"', "\"\""));
+        output.push('"');
+    } else {
+        output.push_str(field);
+    }
+}
@@ -0,0 +1,169 @@
+//! Native file format of the Cells task, which stores everything needed to restore a grid as
+//! JSON:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "column_widths": { "B": 120.0 },
+//!   "names": { "prices": "$B$1:$B$9" },
+//!   "cells": [
+//!     { "coord": "A0", "input": "Total" },
+//!     {
+//!       "coord": "B0",
+//!       "input": "=sum(B1:B9)",
+//!       "format": { "align": "right", "number": { "kind": "fixed", "decimals": 2 } }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Only cells with input or formatting, columns with a custom width and defined names are
+//! written. The version is increased with every incompatible change to the format.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    io,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{
+    format::CellFormat,
+    names::{NameTarget, Names},
+    Coord,
+};
+
+/// Version of the format written by this build. Files with a newer version are rejected.
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    pub version: u32,
+    /// Widths of columns by column name, e.g. `"B"`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub column_widths: BTreeMap<String, f32>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub names: BTreeMap<String, NameTarget>,
+    #[serde(default)]
+    pub cells: Vec<CellDocument>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CellDocument {
+    #[serde(
+        serialize_with = "serialize_coord",
+        deserialize_with = "deserialize_coord"
+    )]
+    pub coord: Coord,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub input: String,
+    #[serde(default, skip_serializing_if = "CellFormat::is_default")]
+    pub format: CellFormat,
+}
+
+impl Document {
+    pub fn new() -> Self {
+        Self {
+            version: VERSION,
+            column_widths: BTreeMap::new(),
+            names: BTreeMap::new(),
+            cells: Vec::new(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("documents only contain serializable data")
+    }
+
+    /// Reads a document, checking the version before anything else so that files written by a
+    /// newer build are reported as such instead of as corrupted.
+    pub fn from_json(input: &str) -> Result<Self, DocumentError> {
+        #[derive(Deserialize)]
+        struct Header {
+            version: u32,
+        }
+
+        let header: Header = serde_json::from_str(input).map_err(DocumentError::Corrupted)?;
+        if header.version > VERSION {
+            return Err(DocumentError::UnsupportedVersion(header.version));
+        }
+        let document: Document = serde_json::from_str(input).map_err(DocumentError::Corrupted)?;
+        if let Some(name) = document.names.keys().find(|name| !Names::is_valid(name)) {
+            let error = serde::de::Error::custom(format!("invalid name `{}`", name));
+            return Err(DocumentError::Corrupted(error));
+        }
+        Ok(document)
+    }
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug)]
+pub enum DocumentError {
+    Io(io::Error),
+    /// The file is not a valid document.
+    Corrupted(serde_json::Error),
+    /// The file was written by a newer version of the application.
+    UnsupportedVersion(u32),
+}
+
+impl Display for DocumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocumentError::Io(error) => error.fmt(f),
+            DocumentError::Corrupted(error) => write!(f, "the file is corrupted ({})", error),
+            DocumentError::UnsupportedVersion(version) => write!(
+                f,
+                "the file has version {}, but only versions up to {} are supported",
+                version, VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DocumentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DocumentError::Io(error) => Some(error),
+            DocumentError::Corrupted(error) => Some(error),
+            DocumentError::UnsupportedVersion(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for DocumentError {
+    fn from(error: io::Error) -> Self {
+        DocumentError::Io(error)
+    }
+}
+
+fn serialize_coord<S: Serializer>(coord: &Coord, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(coord)
+}
+
+fn deserialize_coord<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Coord, D::Error> {
+    let coord = String::deserialize(deserializer)?;
+    coord
+        .parse()
+        .map_err(|_| serde::de::Error::custom(format!("invalid cell `{}`", coord)))
+}
+
+impl Serialize for NameTarget {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for NameTarget {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let target = String::deserialize(deserializer)?;
+        target
+            .parse()
+            .map_err(|_| serde::de::Error::custom(format!("invalid name target `{}`", target)))
+    }
+}
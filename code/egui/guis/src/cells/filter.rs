@@ -0,0 +1,180 @@
+//! Auto-filters, which hide the rows of a range whose values do not match the criteria given for
+//! its columns.
+
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use super::{
+    structure::{Axis, StructureChange},
+    value::{self, CellValue},
+    Coord,
+};
+
+/// Filter of the rows below the header row of a range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoFilter {
+    /// Top left corner, which is the first cell of the header row.
+    start: Coord,
+    /// Bottom right corner.
+    end: Coord,
+    criteria: BTreeMap<usize, Criterion>,
+}
+
+impl AutoFilter {
+    /// Filter without criteria of the range between `corner` and `other_corner`, where the top row
+    /// is the header.
+    pub fn new(corner: Coord, other_corner: Coord) -> Self {
+        Self {
+            start: Coord {
+                column: corner.column.min(other_corner.column),
+                row: corner.row.min(other_corner.row),
+            },
+            end: Coord {
+                column: corner.column.max(other_corner.column),
+                row: corner.row.max(other_corner.row),
+            },
+            criteria: BTreeMap::new(),
+        }
+    }
+
+    pub fn start(&self) -> Coord {
+        self.start
+    }
+
+    pub fn end(&self) -> Coord {
+        self.end
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = usize> {
+        self.start.column..=self.end.column
+    }
+
+    /// Text of the criterion of `column`, which is empty if the column is not filtered.
+    pub fn criterion(&self, column: usize) -> &str {
+        self.criteria
+            .get(&column)
+            .map_or("", |criterion| criterion.text.as_str())
+    }
+
+    pub fn set_criterion(&mut self, column: usize, text: &str) {
+        if text.trim().is_empty() {
+            self.criteria.remove(&column);
+        } else {
+            self.criteria.insert(column, Criterion::new(text));
+        }
+    }
+
+    /// Whether `row` is below the header and its values do not match all criteria.
+    pub fn is_hidden<'a>(&self, row: usize, value: impl Fn(Coord) -> &'a CellValue) -> bool {
+        row > self.start.row
+            && row <= self.end.row
+            && self
+                .criteria
+                .iter()
+                .any(|(&column, criterion)| !criterion.matches(value(Coord { column, row })))
+    }
+
+    /// The filter after inserting or deleting rows or columns, or `None` if its header row or
+    /// all of its columns are deleted.
+    pub fn moved(&self, change: &StructureChange) -> Option<AutoFilter> {
+        if change.axis() == Axis::Rows {
+            change.move_coord(self.start)?;
+        }
+        let (start, end) = change.move_range(self.start, self.end)?;
+        let criteria = self
+            .criteria
+            .iter()
+            .filter_map(|(&column, criterion)| {
+                let coord = change.move_coord(Coord {
+                    column,
+                    row: self.start.row,
+                })?;
+                Some((coord.column, criterion.clone()))
+            })
+            .collect();
+        Some(AutoFilter {
+            start,
+            end,
+            criteria,
+        })
+    }
+}
+
+/// Condition for the values of a column: text to search for, or a comparison like `>10`,
+/// `<>done` or `=` for empty cells.
+#[derive(Debug, Clone, PartialEq)]
+struct Criterion {
+    text: String,
+    operator: Operator,
+    operand: CellValue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Contains,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl Criterion {
+    fn new(text: &str) -> Self {
+        let trimmed = text.trim();
+        let (operator, operand) = [
+            ("<>", Operator::NotEqual),
+            ("<=", Operator::LessEqual),
+            (">=", Operator::GreaterEqual),
+            ("<", Operator::Less),
+            (">", Operator::Greater),
+            ("=", Operator::Equal),
+        ]
+        .iter()
+        .find_map(|&(prefix, operator)| Some((operator, trimmed.strip_prefix(prefix)?)))
+        .unwrap_or((Operator::Contains, trimmed));
+        let operand = operand.trim();
+        let operand = if operator == Operator::Contains {
+            CellValue::Text(operand.to_string())
+        } else if operand.is_empty() {
+            CellValue::Empty
+        } else if let Some(number) = value::parse_number(operand) {
+            CellValue::Number(number)
+        } else if operand.eq_ignore_ascii_case("true") || operand.eq_ignore_ascii_case("false") {
+            CellValue::Boolean(operand.eq_ignore_ascii_case("true"))
+        } else {
+            CellValue::Text(operand.to_string())
+        };
+        Self {
+            text: text.to_string(),
+            operator,
+            operand,
+        }
+    }
+
+    /// Text is compared case insensitively, and values of different types are never less or
+    /// greater than each other.
+    fn matches(&self, value: &CellValue) -> bool {
+        let ordering = || match (value, &self.operand) {
+            (CellValue::Empty, CellValue::Empty) => Some(Ordering::Equal),
+            (CellValue::Number(_), CellValue::Number(_))
+            | (CellValue::Text(_), CellValue::Text(_))
+            | (CellValue::Boolean(_), CellValue::Boolean(_)) => value.compare(&self.operand).ok(),
+            _ => None,
+        };
+        match self.operator {
+            Operator::Contains => value
+                .to_string()
+                .to_lowercase()
+                .contains(&self.operand.to_string().to_lowercase()),
+            Operator::Equal => ordering() == Some(Ordering::Equal),
+            Operator::NotEqual => ordering() != Some(Ordering::Equal),
+            Operator::Less => ordering() == Some(Ordering::Less),
+            Operator::LessEqual => matches!(ordering(), Some(Ordering::Less | Ordering::Equal)),
+            Operator::Greater => ordering() == Some(Ordering::Greater),
+            Operator::GreaterEqual => {
+                matches!(ordering(), Some(Ordering::Greater | Ordering::Equal))
+            }
+        }
+    }
+}
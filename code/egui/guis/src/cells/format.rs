@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+use time::{Date, Month};
+
+use super::value::CellValue;
+
+/// How a cell is displayed. Formatting never changes the value used by formulas.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CellFormat {
+    pub align: Align,
+    #[serde(skip_serializing_if = "NumberFormat::is_general")]
+    pub number: NumberFormat,
+}
+
+impl CellFormat {
+    pub fn is_default(&self) -> bool {
+        *self == CellFormat::default()
+    }
+
+    /// Alignment of the value, where [`Align::Auto`] puts numbers on the right, booleans and
+    /// errors in the center and everything else on the left.
+    pub fn resolve_align(&self, value: &CellValue) -> Align {
+        match (self.align, value) {
+            (Align::Auto, CellValue::Number(_)) => Align::Right,
+            (Align::Auto, CellValue::Boolean(_) | CellValue::Error(_)) => Align::Center,
+            (Align::Auto, _) => Align::Left,
+            (align, _) => align,
+        }
+    }
+
+    /// Text shown for `value`, where only numbers are affected by the number format.
+    pub fn display(&self, value: &CellValue) -> String {
+        match value {
+            CellValue::Number(number) => self.number.format(*number),
+            value => value.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Align {
+    #[default]
+    Auto,
+    Left,
+    Center,
+    Right,
+}
+
+impl Align {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Align::Auto => "Auto",
+            Align::Left => "Left",
+            Align::Center => "Center",
+            Align::Right => "Right",
+        }
+    }
+}
+
+/// How numbers are displayed, e.g. `1234.5` as `1234.50`, `1,234.50`, `123450%`, `1.23E+03` or,
+/// as a date, `1903-05-18`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum NumberFormat {
+    /// As many decimals as needed.
+    #[default]
+    General,
+    Fixed {
+        decimals: u8,
+    },
+    /// Fixed decimals with `,` between groups of thousands.
+    Thousands {
+        decimals: u8,
+    },
+    /// The number times 100 with a `%` sign.
+    Percent {
+        decimals: u8,
+    },
+    /// One digit before the decimal point and a power of ten.
+    Scientific {
+        decimals: u8,
+    },
+    /// Whole days since 1899-12-30 as in other spreadsheets, so that `1` is 1899-12-31.
+    Date,
+}
+
+impl NumberFormat {
+    /// Formats with `decimals` decimals where applicable, in the order they are offered.
+    pub fn all(decimals: u8) -> [NumberFormat; 6] {
+        [
+            NumberFormat::General,
+            NumberFormat::Fixed { decimals },
+            NumberFormat::Thousands { decimals },
+            NumberFormat::Percent { decimals },
+            NumberFormat::Scientific { decimals },
+            NumberFormat::Date,
+        ]
+    }
+
+    pub fn is_general(&self) -> bool {
+        *self == NumberFormat::General
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NumberFormat::General => "General",
+            NumberFormat::Fixed { .. } => "Fixed",
+            NumberFormat::Thousands { .. } => "Thousands",
+            NumberFormat::Percent { .. } => "Percent",
+            NumberFormat::Scientific { .. } => "Scientific",
+            NumberFormat::Date => "Date",
+        }
+    }
+
+    pub fn decimals(&self) -> Option<u8> {
+        match *self {
+            NumberFormat::Fixed { decimals }
+            | NumberFormat::Thousands { decimals }
+            | NumberFormat::Percent { decimals }
+            | NumberFormat::Scientific { decimals } => Some(decimals),
+            NumberFormat::General | NumberFormat::Date => None,
+        }
+    }
+
+    pub fn decimals_mut(&mut self) -> Option<&mut u8> {
+        match self {
+            NumberFormat::Fixed { decimals }
+            | NumberFormat::Thousands { decimals }
+            | NumberFormat::Percent { decimals }
+            | NumberFormat::Scientific { decimals } => Some(decimals),
+            NumberFormat::General | NumberFormat::Date => None,
+        }
+    }
+
+    /// Formats `number`, falling back to the general format for dates outside of the calendar.
+    pub fn format(&self, number: f64) -> String {
+        match *self {
+            NumberFormat::General => number.to_string(),
+            NumberFormat::Fixed { decimals } => format!("{:.*}", decimals as usize, number),
+            NumberFormat::Thousands { decimals } => {
+                group_thousands(&format!("{:.*}", decimals as usize, number))
+            }
+            NumberFormat::Percent { decimals } => {
+                format!("{:.*}%", decimals as usize, number * 100.0)
+            }
+            NumberFormat::Scientific { decimals } => scientific(number, decimals),
+            NumberFormat::Date => date(number)
+                .map(|date| {
+                    format!(
+                        "{:04}-{:02}-{:02}",
+                        date.year(),
+                        date.month() as u8,
+                        date.day()
+                    )
+                })
+                .unwrap_or_else(|| number.to_string()),
+        }
+    }
+}
+
+/// Inserts `,` between groups of three digits before the decimal point of a formatted number.
+fn group_thousands(formatted: &str) -> String {
+    let (sign, unsigned) = match formatted.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", formatted),
+    };
+    let (integer, fraction) = match unsigned.find('.') {
+        Some(point) => unsigned.split_at(point),
+        None => (unsigned, ""),
+    };
+    let mut grouped = String::from(sign);
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped.push_str(fraction);
+    grouped
+}
+
+/// Formats like `1.23E+03` instead of Rust's `1.23e3`.
+fn scientific(number: f64, decimals: u8) -> String {
+    let formatted = format!("{:.*e}", decimals as usize, number);
+    match formatted.split_once('e') {
+        Some((mantissa, exponent)) => {
+            let (sign, digits) = match exponent.strip_prefix('-') {
+                Some(digits) => ('-', digits),
+                None => ('+', exponent),
+            };
+            format!("{}E{}{:0>2}", mantissa, sign, digits)
+        }
+        None => formatted,
+    }
+}
+
+fn date(serial: f64) -> Option<Date> {
+    if !serial.is_finite() || serial.abs() > i32::MAX as f64 {
+        return None;
+    }
+    let epoch = Date::from_calendar_date(1899, Month::December, 30).ok()?;
+    let day = epoch.to_julian_day().checked_add(serial.floor() as i32)?;
+    Date::from_julian_day(day).ok()
+}
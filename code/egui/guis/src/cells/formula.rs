@@ -0,0 +1,903 @@
+//! Parser for the formula language of the Cells task.
+//!
+//! A formula is the content of a cell starting with `=`, for example `=sum(A1:B5) * 2 + C3`.
+//! Parsing never fails as a whole: invalid parts of the input are replaced with
+//! [`ExprKind::Error`] nodes and reported as [`ParseError`]s, which point to the offending part
+//! of the input with a byte span.
+//!
+//! References can mark their column or row as absolute with `$`, e.g. `$A$1` or `A$1`, which
+//! only matters when a formula is copied to another cell with [`adjust_references`].
+
+use std::{
+    fmt::{self, Display},
+    ops::Range,
+    str::FromStr,
+};
+
+use super::{names::Names, Area, Coord};
+
+/// Byte range into the parsed input.
+pub type Span = Range<usize>;
+
+/// Result of parsing a formula. The expression is only meaningful if there are no errors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    pub expr: Expr,
+    pub errors: Vec<ParseError>,
+}
+
+impl Formula {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+impl Expr {
+    /// Areas read by the expression: a single cell for every reference and the whole rectangle
+    /// for every range.
+    pub fn precedents(&self) -> Vec<Area> {
+        let mut precedents = Vec::new();
+        self.collect_precedents(&mut precedents);
+        precedents
+    }
+
+    /// Names used by the expression, in lowercase.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.visit(&mut |expr| {
+            if let ExprKind::Name(name) = &expr.kind {
+                names.push(name.clone());
+            }
+        });
+        names
+    }
+
+    /// Calls `f` with the expression and all of its subexpressions.
+    fn visit(&self, f: &mut impl FnMut(&Expr)) {
+        f(self);
+        match &self.kind {
+            ExprKind::Unary(_, operand) => operand.visit(f),
+            ExprKind::Binary(_, lhs, rhs) => {
+                lhs.visit(f);
+                rhs.visit(f);
+            }
+            ExprKind::Call(_, args) => {
+                for arg in args {
+                    arg.visit(f);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn collect_precedents(&self, precedents: &mut Vec<Area>) {
+        match &self.kind {
+            ExprKind::Ref(reference) => precedents.push(Area::cell(reference.coord)),
+            ExprKind::Range(start, end) => precedents.push(Area::new(start.coord, end.coord)),
+            ExprKind::Unary(_, operand) => operand.collect_precedents(precedents),
+            ExprKind::Binary(_, lhs, rhs) => {
+                lhs.collect_precedents(precedents);
+                rhs.collect_precedents(precedents);
+            }
+            ExprKind::Call(_, args) => {
+                for arg in args {
+                    arg.collect_precedents(precedents);
+                }
+            }
+            ExprKind::Number(_)
+            | ExprKind::Text(_)
+            | ExprKind::Boolean(_)
+            | ExprKind::Name(_)
+            | ExprKind::InvalidRef
+            | ExprKind::Error => {}
+        }
+    }
+}
+
+/// Writes the expression as formula text without the leading `=`, with only the parentheses
+/// needed to parse the text into the same expression again.
+impl Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ExprKind::Number(number) => write!(f, "{}", number),
+            ExprKind::Text(text) => write!(f, "\"{}\"", text.replace('"', "\"\"")),
+            ExprKind::Boolean(true) => f.write_str("TRUE"),
+            ExprKind::Boolean(false) => f.write_str("FALSE"),
+            ExprKind::Ref(reference) => reference.fmt(f),
+            ExprKind::Range(start, end) => write!(f, "{}:{}", start, end),
+            ExprKind::Name(name) => f.write_str(name),
+            ExprKind::InvalidRef => f.write_str("#REF"),
+            ExprKind::Unary(op, operand) => {
+                f.write_str(op.as_str())?;
+                operand.fmt_operand(f, UNARY_PRECEDENCE)
+            }
+            // The base of a power is parsed as a single value, e.g. `(-2)^2`.
+            ExprKind::Binary(BinaryOp::Pow, base, exponent) => {
+                base.fmt_operand(f, PRIMARY_PRECEDENCE)?;
+                f.write_str(" ^ ")?;
+                exponent.fmt_operand(f, UNARY_PRECEDENCE)
+            }
+            // Operators are left associative, so only the right operand needs parentheses if it
+            // has the same precedence, e.g. `1 - (2 - 3)`.
+            ExprKind::Binary(op, lhs, rhs) => {
+                lhs.fmt_operand(f, op.precedence())?;
+                write!(f, " {} ", op.as_str())?;
+                rhs.fmt_operand(f, op.precedence() + 1)
+            }
+            ExprKind::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                for (index, arg) in args.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    arg.fmt(f)?;
+                }
+                f.write_str(")")
+            }
+            ExprKind::Error => f.write_str("#PARSE"),
+        }
+    }
+}
+
+const UNARY_PRECEDENCE: u8 = 5;
+const PRIMARY_PRECEDENCE: u8 = 7;
+
+impl Expr {
+    fn precedence(&self) -> u8 {
+        match &self.kind {
+            ExprKind::Binary(op, ..) => op.precedence(),
+            ExprKind::Unary(..) => UNARY_PRECEDENCE,
+            _ => PRIMARY_PRECEDENCE,
+        }
+    }
+
+    /// Writes the expression in parentheses if it binds less tightly than `precedence`.
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>, precedence: u8) -> fmt::Result {
+        if self.precedence() < precedence {
+            write!(f, "({})", self)
+        } else {
+            self.fmt(f)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Number(f64),
+    Text(String),
+    Boolean(bool),
+    Ref(CellRef),
+    /// Rectangular area between two corners, both included.
+    Range(CellRef, CellRef),
+    /// Name of a cell or range in lowercase, which might not be defined.
+    Name(String),
+    /// Reference to a cell that does not exist anymore, written as `#REF`.
+    InvalidRef,
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+    /// Placeholder for a part of the input that could not be parsed.
+    Error,
+}
+
+/// Reference to a cell as written in a formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CellRef {
+    pub coord: Coord,
+    /// The column is written with `$` and is kept when the formula is copied.
+    pub absolute_column: bool,
+    /// The row is written with `$` and is kept when the formula is copied.
+    pub absolute_row: bool,
+}
+
+impl CellRef {
+    pub fn relative(coord: Coord) -> Self {
+        Self {
+            coord,
+            absolute_column: false,
+            absolute_row: false,
+        }
+    }
+
+    /// Moves the relative parts of the reference by the given number of columns and rows.
+    /// Returns `None` if the reference would leave the grid.
+    pub fn offset(self, columns: isize, rows: isize) -> Option<Self> {
+        let columns = if self.absolute_column { 0 } else { columns };
+        let rows = if self.absolute_row { 0 } else { rows };
+        Some(Self {
+            coord: self.coord.offset(columns, rows)?,
+            ..self
+        })
+    }
+}
+
+impl FromStr for CellRef {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (absolute_column, s) = strip_dollar(s);
+        let split = s
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(s.len());
+        let (column, row) = s.split_at(split);
+        let (absolute_row, row) = strip_dollar(row);
+        Ok(Self {
+            coord: Coord::from_parts(column, row).ok_or(())?,
+            absolute_column,
+            absolute_row,
+        })
+    }
+}
+
+fn strip_dollar(s: &str) -> (bool, &str) {
+    match s.strip_prefix('$') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    }
+}
+
+impl Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dollar = |absolute| if absolute { "$" } else { "" };
+        write!(
+            f,
+            "{}{}{}{}",
+            dollar(self.absolute_column),
+            Coord::column_name(self.coord.column),
+            dollar(self.absolute_row),
+            self.coord.row
+        )
+    }
+}
+
+/// Reference to a cell or range as handed to [`adjust_references`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reference {
+    Cell(CellRef),
+    Range(CellRef, CellRef),
+}
+
+impl Reference {
+    /// Moves the relative parts of the reference, see [`CellRef::offset`].
+    pub fn offset(self, columns: isize, rows: isize) -> Option<Self> {
+        match self {
+            Reference::Cell(reference) => Some(Reference::Cell(reference.offset(columns, rows)?)),
+            Reference::Range(start, end) => Some(Reference::Range(
+                start.offset(columns, rows)?,
+                end.offset(columns, rows)?,
+            )),
+        }
+    }
+}
+
+impl Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reference::Cell(reference) => reference.fmt(f),
+            Reference::Range(start, end) => write!(f, "{}:{}", start, end),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Plus,
+    Neg,
+}
+
+impl UnaryOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnaryOp::Plus => "+",
+            UnaryOp::Neg => "-",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Concat,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl BinaryOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Pow => "^",
+            BinaryOp::Concat => "&",
+            BinaryOp::Eq => "=",
+            BinaryOp::Ne => "<>",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+        }
+    }
+
+    /// How tightly the operator binds, see [`Parser`].
+    fn precedence(&self) -> u8 {
+        match self {
+            BinaryOp::Eq
+            | BinaryOp::Ne
+            | BinaryOp::Lt
+            | BinaryOp::Le
+            | BinaryOp::Gt
+            | BinaryOp::Ge => 1,
+            BinaryOp::Concat => 2,
+            BinaryOp::Add | BinaryOp::Sub => 3,
+            BinaryOp::Mul | BinaryOp::Div => 4,
+            BinaryOp::Pow => 6,
+        }
+    }
+}
+
+/// Parses a formula. A leading `=` is skipped, so that spans can refer to the cell input
+/// directly.
+pub fn parse(input: &str) -> Formula {
+    let start = if input.starts_with('=') { 1 } else { 0 };
+    let (tokens, errors) = lex(input, start);
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        errors,
+        input_len: input.len(),
+    };
+
+    let expr = parser.expr();
+    let token = parser.peek();
+    if token.kind != TokenKind::Eof {
+        let span = token.span.start..input.len();
+        parser.error(format!("unexpected `{}`", &input[span.clone()]), span);
+    }
+
+    let mut errors = parser.errors;
+    errors.sort_by_key(|e| e.span.start);
+    Formula { expr, errors }
+}
+
+/// Rewrites every reference of a formula with `adjust` and keeps the rest of the input as it
+/// was written. References for which `adjust` returns `None` are replaced with `#REF`.
+pub fn adjust_references(
+    input: &str,
+    mut adjust: impl FnMut(Reference) -> Option<Reference>,
+) -> String {
+    let start = if input.starts_with('=') { 1 } else { 0 };
+    let (tokens, _) = lex(input, start);
+    let reference = |index: usize| match &tokens[index].kind {
+        // Function names can look like references, too.
+        TokenKind::Ident(name) if tokens[index + 1].kind != TokenKind::LParen => name.parse().ok(),
+        _ => None,
+    };
+
+    let mut output = String::new();
+    let mut copied = 0;
+    let mut index = 0;
+    while index < tokens.len() {
+        let start = match reference(index) {
+            Some(start) => start,
+            None => {
+                index += 1;
+                continue;
+            }
+        };
+        let range_end = match tokens[index + 1].kind {
+            TokenKind::Colon => reference(index + 2),
+            _ => None,
+        };
+        let (span, reference) = match range_end {
+            Some(end) => {
+                index += 3;
+                let span = tokens[index - 3].span.start..tokens[index - 1].span.end;
+                (span, Reference::Range(start, end))
+            }
+            None => {
+                index += 1;
+                (tokens[index - 1].span.clone(), Reference::Cell(start))
+            }
+        };
+        output.push_str(&input[copied..span.start]);
+        match adjust(reference) {
+            Some(reference) => output.push_str(&reference.to_string()),
+            None => output.push_str("#REF"),
+        }
+        copied = span.end;
+    }
+    output.push_str(&input[copied..]);
+    output
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Number(f64),
+    Text(String),
+    Ident(String),
+    /// `#REF`
+    InvalidRef,
+    LParen,
+    RParen,
+    Comma,
+    Colon,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Caret,
+    Ampersand,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    span: Span,
+}
+
+fn lex(input: &str, start: usize) -> (Vec<Token>, Vec<ParseError>) {
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    let mut position = start;
+
+    let take_while = |mut position: usize, predicate: fn(u8) -> bool| {
+        while bytes.get(position).is_some_and(|&c| predicate(c)) {
+            position += 1;
+        }
+        position
+    };
+
+    while position < bytes.len() {
+        let start = position;
+        let kind = match bytes[position] {
+            c if c.is_ascii_whitespace() => {
+                position += 1;
+                continue;
+            }
+            b'0'..=b'9' | b'.' => {
+                position = take_while(position, |c| c.is_ascii_digit() || c == b'.');
+                if let Some(b'e' | b'E') = bytes.get(position) {
+                    let mut exponent = position + 1;
+                    if let Some(b'+' | b'-') = bytes.get(exponent) {
+                        exponent += 1;
+                    }
+                    if bytes.get(exponent).is_some_and(u8::is_ascii_digit) {
+                        position = take_while(exponent, |c| c.is_ascii_digit());
+                    }
+                }
+                let text = &input[start..position];
+                match text.parse::<f64>() {
+                    Ok(number) if number.is_finite() => TokenKind::Number(number),
+                    // Infinity would be printed as `inf`, which is a name.
+                    Ok(_) => {
+                        errors.push(ParseError {
+                            message: format!("number `{}` is too large", text),
+                            span: start..position,
+                        });
+                        TokenKind::Number(f64::NAN)
+                    }
+                    Err(_) => {
+                        errors.push(ParseError {
+                            message: format!("invalid number `{}`", text),
+                            span: start..position,
+                        });
+                        TokenKind::Number(f64::NAN)
+                    }
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' || c == b'$' => {
+                position = take_while(position, |c| {
+                    c.is_ascii_alphanumeric() || c == b'_' || c == b'$'
+                });
+                TokenKind::Ident(input[start..position].to_string())
+            }
+            b'#' if input[position..]
+                .get(..4)
+                .is_some_and(|s| s.eq_ignore_ascii_case("#REF")) =>
+            {
+                position += 4;
+                TokenKind::InvalidRef
+            }
+            b'"' => {
+                let mut text = String::new();
+                position += 1;
+                loop {
+                    match input[position..].find('"') {
+                        Some(offset) => {
+                            text.push_str(&input[position..position + offset]);
+                            position += offset + 1;
+                            // Two quotes in a row are an escaped quote.
+                            if bytes.get(position) == Some(&b'"') {
+                                text.push('"');
+                                position += 1;
+                            } else {
+                                break;
+                            }
+                        }
+                        None => {
+                            text.push_str(&input[position..]);
+                            position = input.len();
+                            errors.push(ParseError {
+                                message: "unterminated text".to_string(),
+                                span: start..position,
+                            });
+                            break;
+                        }
+                    }
+                }
+                TokenKind::Text(text)
+            }
+            c => {
+                position += 1;
+                let next = bytes.get(position).copied();
+                let (kind, len) = match (c, next) {
+                    (b'<', Some(b'=')) => (TokenKind::Le, 1),
+                    (b'<', Some(b'>')) => (TokenKind::Ne, 1),
+                    (b'>', Some(b'=')) => (TokenKind::Ge, 1),
+                    (b'(', _) => (TokenKind::LParen, 0),
+                    (b')', _) => (TokenKind::RParen, 0),
+                    (b',', _) => (TokenKind::Comma, 0),
+                    (b':', _) => (TokenKind::Colon, 0),
+                    (b'+', _) => (TokenKind::Plus, 0),
+                    (b'-', _) => (TokenKind::Minus, 0),
+                    (b'*', _) => (TokenKind::Star, 0),
+                    (b'/', _) => (TokenKind::Slash, 0),
+                    (b'^', _) => (TokenKind::Caret, 0),
+                    (b'&', _) => (TokenKind::Ampersand, 0),
+                    (b'=', _) => (TokenKind::Eq, 0),
+                    (b'<', _) => (TokenKind::Lt, 0),
+                    (b'>', _) => (TokenKind::Gt, 0),
+                    _ => {
+                        // Skip the whole character, which might be longer than a byte.
+                        let len = input[start..].chars().next().map_or(1, char::len_utf8);
+                        position = start + len;
+                        errors.push(ParseError {
+                            message: format!("unexpected character `{}`", &input[start..position]),
+                            span: start..position,
+                        });
+                        continue;
+                    }
+                };
+                position += len;
+                kind
+            }
+        };
+        tokens.push(Token {
+            kind,
+            span: start..position,
+        });
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        span: input.len()..input.len(),
+    });
+    (tokens, errors)
+}
+
+/// Recursive descent parser with one token lookahead. Operators from lowest to highest
+/// precedence: comparisons, `&`, `+ -`, `* /`, unary `+ -` and `^`.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    errors: Vec<ParseError>,
+    input_len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        if token.kind != TokenKind::Eof {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn error(&mut self, message: String, span: Span) {
+        self.errors.push(ParseError { message, span });
+    }
+
+    fn expr(&mut self) -> Expr {
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Expr {
+        let mut lhs = self.concat();
+        loop {
+            let op = match self.peek().kind {
+                TokenKind::Eq => BinaryOp::Eq,
+                TokenKind::Ne => BinaryOp::Ne,
+                TokenKind::Lt => BinaryOp::Lt,
+                TokenKind::Le => BinaryOp::Le,
+                TokenKind::Gt => BinaryOp::Gt,
+                TokenKind::Ge => BinaryOp::Ge,
+                _ => return lhs,
+            };
+            self.next();
+            let rhs = self.concat();
+            lhs = binary(op, lhs, rhs);
+        }
+    }
+
+    fn concat(&mut self) -> Expr {
+        let mut lhs = self.additive();
+        while self.peek().kind == TokenKind::Ampersand {
+            self.next();
+            let rhs = self.additive();
+            lhs = binary(BinaryOp::Concat, lhs, rhs);
+        }
+        lhs
+    }
+
+    fn additive(&mut self) -> Expr {
+        let mut lhs = self.multiplicative();
+        loop {
+            let op = match self.peek().kind {
+                TokenKind::Plus => BinaryOp::Add,
+                TokenKind::Minus => BinaryOp::Sub,
+                _ => return lhs,
+            };
+            self.next();
+            let rhs = self.multiplicative();
+            lhs = binary(op, lhs, rhs);
+        }
+    }
+
+    fn multiplicative(&mut self) -> Expr {
+        let mut lhs = self.unary();
+        loop {
+            let op = match self.peek().kind {
+                TokenKind::Star => BinaryOp::Mul,
+                TokenKind::Slash => BinaryOp::Div,
+                _ => return lhs,
+            };
+            self.next();
+            let rhs = self.unary();
+            lhs = binary(op, lhs, rhs);
+        }
+    }
+
+    fn unary(&mut self) -> Expr {
+        let op = match self.peek().kind {
+            TokenKind::Plus => UnaryOp::Plus,
+            TokenKind::Minus => UnaryOp::Neg,
+            _ => return self.power(),
+        };
+        let start = self.next().span.start;
+        let operand = self.unary();
+        Expr {
+            span: start..operand.span.end,
+            kind: ExprKind::Unary(op, Box::new(operand)),
+        }
+    }
+
+    fn power(&mut self) -> Expr {
+        let base = self.primary();
+        if self.peek().kind == TokenKind::Caret {
+            self.next();
+            // Right associative: `2^3^2` is `2^(3^2)`.
+            let exponent = self.unary();
+            return binary(BinaryOp::Pow, base, exponent);
+        }
+        base
+    }
+
+    fn primary(&mut self) -> Expr {
+        let token = self.peek().clone();
+        match token.kind {
+            TokenKind::Number(number) => {
+                self.next();
+                Expr {
+                    kind: ExprKind::Number(number),
+                    span: token.span,
+                }
+            }
+            TokenKind::Text(text) => {
+                self.next();
+                Expr {
+                    kind: ExprKind::Text(text),
+                    span: token.span,
+                }
+            }
+            TokenKind::Ident(name) => {
+                self.next();
+                match self.peek().kind {
+                    TokenKind::LParen => self.call(name, token.span),
+                    TokenKind::Colon => self.range(&name, token.span),
+                    _ if name.eq_ignore_ascii_case("true") => Expr {
+                        kind: ExprKind::Boolean(true),
+                        span: token.span,
+                    },
+                    _ if name.eq_ignore_ascii_case("false") => Expr {
+                        kind: ExprKind::Boolean(false),
+                        span: token.span,
+                    },
+                    _ => self.reference(&name, token.span),
+                }
+            }
+            TokenKind::InvalidRef => {
+                self.next();
+                Expr {
+                    kind: ExprKind::InvalidRef,
+                    span: token.span,
+                }
+            }
+            TokenKind::LParen => {
+                self.next();
+                let mut expr = self.expr();
+                let end = self.expect_closing_paren(token.span.start);
+                expr.span = token.span.start..end;
+                expr
+            }
+            TokenKind::RParen | TokenKind::Comma | TokenKind::Eof => {
+                let span = token.span.start..token.span.start;
+                self.error("expected a value".to_string(), token.span);
+                Expr {
+                    kind: ExprKind::Error,
+                    span,
+                }
+            }
+            _ => {
+                self.next();
+                self.error("expected a value".to_string(), token.span.clone());
+                Expr {
+                    kind: ExprKind::Error,
+                    span: token.span,
+                }
+            }
+        }
+    }
+
+    /// Reference to a cell or a name.
+    fn reference(&mut self, name: &str, span: Span) -> Expr {
+        if Names::is_valid(name) {
+            return Expr {
+                kind: ExprKind::Name(name.to_lowercase()),
+                span,
+            };
+        }
+        self.cell_reference(name, span)
+    }
+
+    fn cell_reference(&mut self, name: &str, span: Span) -> Expr {
+        match name.parse::<CellRef>() {
+            Ok(reference) => Expr {
+                kind: ExprKind::Ref(reference),
+                span,
+            },
+            Err(_) => {
+                self.error(format!("unknown reference `{}`", name), span.clone());
+                Expr {
+                    kind: ExprKind::Error,
+                    span,
+                }
+            }
+        }
+    }
+
+    fn range(&mut self, start_name: &str, start_span: Span) -> Expr {
+        let start = self.cell_reference(start_name, start_span.clone());
+        self.next();
+
+        let token = self.peek().clone();
+        let end = match token.kind {
+            TokenKind::Ident(name) => {
+                self.next();
+                self.cell_reference(&name, token.span)
+            }
+            _ => {
+                self.error(
+                    "expected a cell reference after `:`".to_string(),
+                    token.span.clone(),
+                );
+                Expr {
+                    kind: ExprKind::Error,
+                    span: token.span.start..token.span.start,
+                }
+            }
+        };
+
+        let span = start_span.start..end.span.end.max(start_span.end);
+        match (start.kind, end.kind) {
+            (ExprKind::Ref(start), ExprKind::Ref(end)) => Expr {
+                kind: ExprKind::Range(start, end),
+                span,
+            },
+            _ => Expr {
+                kind: ExprKind::Error,
+                span,
+            },
+        }
+    }
+
+    fn call(&mut self, name: String, name_span: Span) -> Expr {
+        let open = self.next().span.start;
+        let mut args = Vec::new();
+
+        if self.peek().kind != TokenKind::RParen {
+            loop {
+                args.push(self.expr());
+                if self.peek().kind == TokenKind::Comma {
+                    self.next();
+                } else {
+                    break;
+                }
+            }
+        }
+
+        let end = self.expect_closing_paren(open);
+        Expr {
+            kind: ExprKind::Call(name, args),
+            span: name_span.start..end,
+        }
+    }
+
+    /// Consumes a `)` and returns the end of the enclosed expression. A missing parenthesis is
+    /// reported but treated as if it was there.
+    fn expect_closing_paren(&mut self, open: usize) -> usize {
+        let token = self.peek().clone();
+        if token.kind == TokenKind::RParen {
+            self.next();
+            return token.span.end;
+        }
+
+        let span = if token.kind == TokenKind::Eof {
+            open..self.input_len
+        } else {
+            token.span
+        };
+        self.error("expected `)`".to_string(), span);
+        self.tokens[self.position - 1].span.end.max(open + 1)
+    }
+}
+
+fn binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+    Expr {
+        span: lhs.span.start..rhs.span.end.max(lhs.span.end),
+        kind: ExprKind::Binary(op, Box::new(lhs), Box::new(rhs)),
+    }
+}
@@ -0,0 +1,206 @@
+//! Functions that can be called from formulas, e.g. `=sum(A1:A5)`.
+
+use std::collections::HashMap;
+
+use super::value::{CellValue, ErrorKind};
+
+/// Evaluated argument of a function call.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Argument {
+    Value(CellValue),
+    /// Values of the cells in a range that are not empty, row by row.
+    Range(Vec<CellValue>),
+}
+
+pub type Function = dyn Fn(&[Argument]) -> CellValue;
+
+/// Functions by name. Names are case insensitive.
+pub struct FunctionRegistry {
+    functions: HashMap<String, Box<Function>>,
+}
+
+impl FunctionRegistry {
+    /// Creates a registry without any functions. Use [`FunctionRegistry::default`] to start with
+    /// the built-in functions.
+    pub fn empty() -> Self {
+        Self {
+            functions: HashMap::new(),
+        }
+    }
+
+    /// Adds a function or replaces an existing one with the same name.
+    pub fn register(&mut self, name: &str, function: impl Fn(&[Argument]) -> CellValue + 'static) {
+        self.functions
+            .insert(name.to_lowercase(), Box::new(function));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Function> {
+        self.functions.get(&name.to_lowercase()).map(Box::as_ref)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.functions.keys().map(String::as_str)
+    }
+}
+
+impl Default for FunctionRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+
+        // Folding from positive zero, because `Iterator::sum` of no floats is `-0`.
+        registry.register("sum", |args| {
+            aggregate(args, |numbers| {
+                Ok(numbers.iter().fold(0.0, |sum, number| sum + number))
+            })
+        });
+        registry.register("prod", |args| {
+            aggregate(args, |numbers| Ok(numbers.iter().product()))
+        });
+        registry.register("avg", |args| {
+            aggregate(args, |numbers| {
+                if numbers.is_empty() {
+                    return Err(ErrorKind::Div0);
+                }
+                Ok(numbers.iter().fold(0.0, |sum, number| sum + number) / numbers.len() as f64)
+            })
+        });
+        registry.register("min", |args| {
+            aggregate(args, |numbers| {
+                Ok(numbers.iter().copied().reduce(f64::min).unwrap_or(0.0))
+            })
+        });
+        registry.register("max", |args| {
+            aggregate(args, |numbers| {
+                Ok(numbers.iter().copied().reduce(f64::max).unwrap_or(0.0))
+            })
+        });
+        registry.register("count", |args| {
+            let count = values(args)
+                .filter(|value| matches!(value, CellValue::Number(_)))
+                .count();
+            CellValue::Number(count as f64)
+        });
+
+        registry.register("if", |args| {
+            let (condition, then, otherwise) = match args {
+                [Argument::Value(condition), Argument::Value(then)] => {
+                    (condition, then, &CellValue::Boolean(false))
+                }
+                [Argument::Value(condition), Argument::Value(then), Argument::Value(otherwise)] => {
+                    (condition, then, otherwise)
+                }
+                _ => return ErrorKind::Value.into(),
+            };
+            match condition.as_boolean() {
+                Ok(true) => then.clone(),
+                Ok(false) => otherwise.clone(),
+                Err(error) => error.into(),
+            }
+        });
+        registry.register("and", |args| {
+            logic(args, |conditions| conditions.iter().all(|&c| c))
+        });
+        registry.register("or", |args| {
+            logic(args, |conditions| conditions.iter().any(|&c| c))
+        });
+        registry.register("not", |args| match args {
+            [Argument::Value(value)] => match value.as_boolean() {
+                Ok(boolean) => CellValue::Boolean(!boolean),
+                Err(error) => error.into(),
+            },
+            _ => ErrorKind::Value.into(),
+        });
+
+        registry.register("round", |args| {
+            let (value, digits) = match args {
+                [Argument::Value(value)] => (value, &CellValue::Number(0.0)),
+                [Argument::Value(value), Argument::Value(digits)] => (value, digits),
+                _ => return ErrorKind::Value.into(),
+            };
+            let round = || {
+                let number = value.as_number()?;
+                let factor = 10f64.powi(digits.as_number()?.trunc() as i32);
+                Ok(CellValue::number((number * factor).round() / factor))
+            };
+            round().unwrap_or_else(CellValue::Error)
+        });
+
+        registry.register("concat", |args| {
+            let mut text = String::new();
+            for value in values(args) {
+                match value.as_text() {
+                    Ok(value) => text.push_str(&value),
+                    Err(error) => return error.into(),
+                }
+            }
+            CellValue::Text(text)
+        });
+
+        registry
+    }
+}
+
+fn values(args: &[Argument]) -> impl Iterator<Item = &CellValue> {
+    args.iter().flat_map(|arg| match arg {
+        Argument::Value(value) => std::slice::from_ref(value),
+        Argument::Range(values) => values.as_slice(),
+    })
+}
+
+/// Numbers of all arguments. Values passed directly are converted, while only numbers are
+/// taken from ranges. Errors are passed on in both cases.
+fn numbers(args: &[Argument]) -> Result<Vec<f64>, ErrorKind> {
+    let mut numbers = Vec::new();
+    for arg in args {
+        match arg {
+            Argument::Value(value) => numbers.push(value.as_number()?),
+            Argument::Range(values) => {
+                for value in values {
+                    match value {
+                        CellValue::Number(number) => numbers.push(*number),
+                        CellValue::Error(error) => return Err(*error),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    Ok(numbers)
+}
+
+fn aggregate(args: &[Argument], reduce: impl Fn(&[f64]) -> Result<f64, ErrorKind>) -> CellValue {
+    match numbers(args).and_then(|numbers| reduce(&numbers)) {
+        Ok(number) => CellValue::number(number),
+        Err(error) => error.into(),
+    }
+}
+
+/// Truth values of all arguments, which are taken like [`numbers`] but also include booleans
+/// in ranges.
+fn booleans(args: &[Argument]) -> Result<Vec<bool>, ErrorKind> {
+    let mut booleans = Vec::new();
+    for arg in args {
+        match arg {
+            Argument::Value(value) => booleans.push(value.as_boolean()?),
+            Argument::Range(values) => {
+                for value in values {
+                    match value {
+                        CellValue::Number(_) | CellValue::Boolean(_) | CellValue::Error(_) => {
+                            booleans.push(value.as_boolean()?)
+                        }
+                        CellValue::Empty | CellValue::Text(_) => {}
+                    }
+                }
+            }
+        }
+    }
+    Ok(booleans)
+}
+
+fn logic(args: &[Argument], combine: impl Fn(&[bool]) -> bool) -> CellValue {
+    match booleans(args) {
+        Ok(booleans) if booleans.is_empty() => ErrorKind::Value.into(),
+        Ok(booleans) => CellValue::Boolean(combine(&booleans)),
+        Err(error) => error.into(),
+    }
+}
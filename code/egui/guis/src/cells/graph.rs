@@ -0,0 +1,321 @@
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use super::{Area, Coord};
+
+/// Tracks which cells a formula reads (its precedents) and, in reverse, which formulas read a
+/// cell (its dependents). Cells without any relationship are not stored.
+///
+/// Ranges are stored as areas instead of every cell they cover, so that the graph holds a
+/// single entry per column for `=SUM(A0:ZZ9999)` instead of millions of edges.
+///
+/// The names used by formulas are tracked the same way, so that the formulas can be updated
+/// when a name changes.
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    precedents: HashMap<Coord, Vec<Area>>,
+    /// Formulas reading a single cell, by that cell.
+    dependents: HashMap<Coord, HashSet<Coord>>,
+    /// Ranges read by formulas and the cells of the formulas, by every column of the range.
+    range_dependents: HashMap<usize, Vec<(Area, Coord)>>,
+    names: HashMap<Coord, HashSet<String>>,
+    name_dependents: HashMap<String, HashSet<Coord>>,
+}
+
+impl DependencyGraph {
+    /// Replaces the areas read by the formula in `coord`.
+    pub fn set_precedents(&mut self, coord: Coord, precedents: impl IntoIterator<Item = Area>) {
+        self.remove_precedents(coord);
+        let mut precedents: Vec<Area> = precedents.into_iter().collect();
+        if precedents.is_empty() {
+            return;
+        }
+        precedents.sort_by_key(|area| {
+            (
+                area.start.row,
+                area.start.column,
+                area.end.row,
+                area.end.column,
+            )
+        });
+        precedents.dedup();
+        for &area in &precedents {
+            if area.start == area.end {
+                self.dependents.entry(area.start).or_default().insert(coord);
+            } else {
+                for column in area.columns() {
+                    self.range_dependents
+                        .entry(column)
+                        .or_default()
+                        .push((area, coord));
+                }
+            }
+        }
+        self.precedents.insert(coord, precedents);
+    }
+
+    fn remove_precedents(&mut self, coord: Coord) {
+        for area in self.precedents.remove(&coord).unwrap_or_default() {
+            if area.start == area.end {
+                if let Some(dependents) = self.dependents.get_mut(&area.start) {
+                    dependents.remove(&coord);
+                    if dependents.is_empty() {
+                        self.dependents.remove(&area.start);
+                    }
+                }
+            } else {
+                for column in area.columns() {
+                    if let Some(ranges) = self.range_dependents.get_mut(&column) {
+                        ranges.retain(|&(_, dependent)| dependent != coord);
+                        if ranges.is_empty() {
+                            self.range_dependents.remove(&column);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Replaces the names used by the formula in `coord`.
+    pub fn set_names(&mut self, coord: Coord, names: impl IntoIterator<Item = String>) {
+        replace_edges(&mut self.names, &mut self.name_dependents, coord, names);
+    }
+
+    /// Cells whose formulas use the name directly.
+    pub fn name_dependents(&self, name: &str) -> impl Iterator<Item = Coord> + '_ {
+        self.name_dependents
+            .get(name)
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+
+    /// Areas that the formula in `coord` reads directly.
+    pub fn precedents(&self, coord: Coord) -> &[Area] {
+        self.precedents.get(&coord).map_or(&[], Vec::as_slice)
+    }
+
+    /// Cells whose formulas read `coord` directly. A formula reading `coord` through several
+    /// ranges is returned once for each of them.
+    pub fn dependents(&self, coord: Coord) -> impl Iterator<Item = Coord> + '_ {
+        let ranges = self
+            .range_dependents
+            .get(&coord.column)
+            .into_iter()
+            .flatten();
+        self.dependents
+            .get(&coord)
+            .into_iter()
+            .flatten()
+            .copied()
+            .chain(
+                ranges
+                    .filter(move |(area, _)| area.contains(coord))
+                    .map(|&(_, dependent)| dependent),
+            )
+    }
+
+    /// Cells of `cells` that the formula in `coord` reads directly, in no particular order.
+    /// Areas larger than `cells` are not expanded, but checked against each of `cells`.
+    fn precedents_in(&self, coord: Coord, cells: &HashSet<Coord>) -> Vec<Coord> {
+        let mut found = Vec::new();
+        for area in self.precedents(coord) {
+            if area.cell_count() <= cells.len() {
+                found.extend(area.coords().filter(|cell| cells.contains(cell)));
+            } else {
+                found.extend(cells.iter().copied().filter(|&cell| area.contains(cell)));
+            }
+        }
+        found.sort_by_key(|cell| (cell.row, cell.column));
+        found.dedup();
+        found
+    }
+
+    /// Returns `coords` and all cells depending on them directly or indirectly, ordered so that
+    /// every cell comes after the cells it reads. Cells referring to each other in a circle
+    /// cannot be ordered and are grouped together instead.
+    pub fn recalculation_order(
+        &self,
+        coords: impl IntoIterator<Item = Coord>,
+    ) -> Vec<Recalculation> {
+        let mut affected = HashSet::new();
+        let mut queue = VecDeque::new();
+        for coord in coords {
+            if affected.insert(coord) {
+                queue.push_back(coord);
+            }
+        }
+        while let Some(current) = queue.pop_front() {
+            for dependent in self.dependents(current) {
+                if affected.insert(dependent) {
+                    queue.push_back(dependent);
+                }
+            }
+        }
+
+        self.strongly_connected_components(&affected)
+            .into_iter()
+            .map(|mut component| {
+                if component.len() == 1 {
+                    let cell = component[0];
+                    if !self.precedents(cell).iter().any(|area| area.contains(cell)) {
+                        return Recalculation::Cell(cell);
+                    }
+                }
+                component.sort_by_key(|cell| (cell.row, cell.column));
+                Recalculation::Cycle(component)
+            })
+            .collect()
+    }
+
+    /// Finds the shortest circular reference starting and ending in `coord` that only goes
+    /// through `cells`, e.g. `[A1, B1, A1]` if `A1` reads `B1` and `B1` reads `A1`.
+    pub fn cycle_path(&self, coord: Coord, cells: &HashSet<Coord>) -> Option<Vec<Coord>> {
+        let mut previous: HashMap<Coord, Coord> = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(coord);
+        while let Some(current) = queue.pop_front() {
+            for precedent in self.precedents_in(current, cells) {
+                if precedent == coord {
+                    let mut path = vec![coord, current];
+                    let mut cell = current;
+                    while let Some(&before) = previous.get(&cell) {
+                        path.push(before);
+                        cell = before;
+                    }
+                    // The path was built backwards.
+                    path.reverse();
+                    return Some(path);
+                }
+                if let Entry::Vacant(entry) = previous.entry(precedent) {
+                    entry.insert(current);
+                    queue.push_back(precedent);
+                }
+            }
+        }
+        None
+    }
+
+    /// Tarjan's algorithm on the subgraph of `cells`, following the edges from a cell to its
+    /// precedents. Components are returned in the order they are completed, which means that
+    /// every component comes after the components it reads from. It is iterative because long
+    /// chains of references would overflow the stack otherwise.
+    fn strongly_connected_components(&self, cells: &HashSet<Coord>) -> Vec<Vec<Coord>> {
+        struct Visit {
+            cell: Coord,
+            precedents: Vec<Coord>,
+            next: usize,
+        }
+
+        let mut components = Vec::new();
+        // Discovery index and lowest reachable index of every visited cell.
+        let mut indices: HashMap<Coord, (usize, usize)> = HashMap::new();
+        let mut stack = Vec::new();
+        let mut on_stack = HashSet::new();
+
+        for &root in cells {
+            if indices.contains_key(&root) {
+                continue;
+            }
+
+            let mut visits: Vec<Visit> = Vec::new();
+            let mut undiscovered = Some(root);
+            loop {
+                if let Some(cell) = undiscovered.take() {
+                    let index = indices.len();
+                    indices.insert(cell, (index, index));
+                    stack.push(cell);
+                    on_stack.insert(cell);
+                    visits.push(Visit {
+                        cell,
+                        precedents: self.precedents_in(cell, cells),
+                        next: 0,
+                    });
+                }
+
+                let visit = match visits.last_mut() {
+                    Some(visit) => visit,
+                    None => break,
+                };
+                let cell = visit.cell;
+                if let Some(&precedent) = visit.precedents.get(visit.next) {
+                    visit.next += 1;
+                    match indices.get(&precedent) {
+                        None => undiscovered = Some(precedent),
+                        Some(&(index, _)) if on_stack.contains(&precedent) => {
+                            let lowlink = &mut indices.get_mut(&cell).unwrap().1;
+                            *lowlink = (*lowlink).min(index);
+                        }
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+
+                visits.pop();
+                let (index, lowlink) = indices[&cell];
+                if let Some(parent) = visits.last() {
+                    let parent_lowlink = &mut indices.get_mut(&parent.cell).unwrap().1;
+                    *parent_lowlink = (*parent_lowlink).min(lowlink);
+                }
+                if index == lowlink {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack.remove(&member);
+                        component.push(member);
+                        if member == cell {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+}
+
+/// Step of a recalculation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recalculation {
+    /// Evaluate the cell.
+    Cell(Coord),
+    /// Cells that refer to each other in a circle and cannot be evaluated.
+    Cycle(Vec<Coord>),
+}
+
+/// Replaces the edges from `from` in `forward` and updates the reverse edges in `backward`.
+fn replace_edges<F, T>(
+    forward: &mut HashMap<F, HashSet<T>>,
+    backward: &mut HashMap<T, HashSet<F>>,
+    from: F,
+    to: impl IntoIterator<Item = T>,
+) where
+    F: Clone + Eq + Hash,
+    T: Clone + Eq + Hash,
+{
+    if let Some(old_to) = forward.remove(&from) {
+        for old in old_to {
+            if let Some(reverse) = backward.get_mut(&old) {
+                reverse.remove(&from);
+                if reverse.is_empty() {
+                    backward.remove(&old);
+                }
+            }
+        }
+    }
+
+    let to: HashSet<T> = to.into_iter().collect();
+    if to.is_empty() {
+        return;
+    }
+    for target in &to {
+        backward
+            .entry(target.clone())
+            .or_default()
+            .insert(from.clone());
+    }
+    forward.insert(from, to);
+}
@@ -0,0 +1,120 @@
+//! Linear undo history of the Cells task, which works like the one of the circle drawer but
+//! records the inputs of cells instead of circles.
+
+use super::{filter::AutoFilter, format::CellFormat, names::Names, Coord};
+
+#[derive(Debug, Default)]
+pub struct CellsHistory {
+    changes: Vec<Change>,
+    applied_changes: usize,
+}
+
+impl CellsHistory {
+    /// No changes are currently applied.
+    pub fn is_empty(&self) -> bool {
+        self.applied_changes == 0
+    }
+
+    /// All recorded changes are currently applied.
+    pub fn is_full(&self) -> bool {
+        self.changes.len() == self.applied_changes
+    }
+
+    /// Records a change that was just applied and forgets all undone changes.
+    pub fn push(&mut self, change: Change) {
+        self.changes.truncate(self.applied_changes);
+        self.changes.push(change);
+        self.applied_changes = self.changes.len();
+    }
+
+    pub fn undo(&mut self) -> Option<&Change> {
+        if self.applied_changes > 0 {
+            self.applied_changes -= 1;
+            return Some(&self.changes[self.applied_changes]);
+        }
+        None
+    }
+
+    pub fn redo(&mut self) -> Option<&Change> {
+        if self.applied_changes < self.changes.len() {
+            let change = Some(&self.changes[self.applied_changes]);
+            self.applied_changes += 1;
+            return change;
+        }
+        None
+    }
+
+    pub fn clear(&mut self) {
+        self.changes.clear();
+        self.applied_changes = 0;
+    }
+}
+
+/// Inputs of every cell affected by one action, like editing a cell or pasting a range, which
+/// is undone and redone as a whole.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub edits: Vec<Edit>,
+    /// Formats of cells changed by the action, e.g. by formatting a selection, sorting or
+    /// inserting rows.
+    pub formats: Vec<FormatEdit>,
+    /// Names and auto-filter before and after the action, if it changed any of them.
+    pub layout: Option<LayoutEdit>,
+}
+
+impl Change {
+    /// Change that reverts this one.
+    pub fn reversed(&self) -> Change {
+        Change {
+            edits: self
+                .edits
+                .iter()
+                .map(|edit| Edit {
+                    coord: edit.coord,
+                    before: edit.after.clone(),
+                    after: edit.before.clone(),
+                })
+                .collect(),
+            formats: self
+                .formats
+                .iter()
+                .map(|format| FormatEdit {
+                    coord: format.coord,
+                    before: format.after.clone(),
+                    after: format.before.clone(),
+                })
+                .collect(),
+            layout: self.layout.as_ref().map(|layout| LayoutEdit {
+                before: layout.after.clone(),
+                after: layout.before.clone(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edit {
+    pub coord: Coord,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatEdit {
+    pub coord: Coord,
+    pub before: CellFormat,
+    pub after: CellFormat,
+}
+
+/// Names and auto-filter of a sheet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub names: Names,
+    pub filter: Option<AutoFilter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutEdit {
+    pub before: Layout,
+    pub after: Layout,
+}
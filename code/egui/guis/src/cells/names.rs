@@ -0,0 +1,149 @@
+//! Names that formulas can use instead of references, e.g. `tax_rate` for `$B$1` or `prices`
+//! for `$C$2:$C$50`. Names are case insensitive.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use super::{formula::CellRef, Area, Coord};
+
+/// Cell or range a name stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameTarget {
+    Cell(Coord),
+    /// Rectangular area between two corners, both included.
+    Range(Coord, Coord),
+}
+
+impl NameTarget {
+    pub fn area(&self) -> Area {
+        match *self {
+            NameTarget::Cell(coord) => Area::cell(coord),
+            NameTarget::Range(start, end) => Area::new(start, end),
+        }
+    }
+}
+
+/// Parses a reference like `B1` or a range like `C2:C50`, where `$` is allowed but not needed
+/// because targets never move.
+impl FromStr for NameTarget {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let corner = |s: &str| s.trim().parse::<CellRef>().map(|reference| reference.coord);
+        match s.split_once(':') {
+            Some((start, end)) => Ok(NameTarget::Range(corner(start)?, corner(end)?)),
+            None => Ok(NameTarget::Cell(corner(s)?)),
+        }
+    }
+}
+
+impl Display for NameTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let absolute = |coord| CellRef {
+            coord,
+            absolute_column: true,
+            absolute_row: true,
+        };
+        match *self {
+            NameTarget::Cell(coord) => absolute(coord).fmt(f),
+            NameTarget::Range(start, end) => write!(f, "{}:{}", absolute(start), absolute(end)),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Names {
+    targets: BTreeMap<String, NameTarget>,
+}
+
+impl Names {
+    /// Whether `name` can be used in formulas: it starts with a letter or `_`, continues with
+    /// letters, digits and `_`, and cannot be mistaken for a reference or a boolean.
+    ///
+    /// Letters followed by digits are a reference even outside of the grid, e.g. `A10000`, so
+    /// that formulas report such references as unknown instead of reading a name.
+    pub fn is_valid(name: &str) -> bool {
+        let mut chars = name.chars();
+        let letters = name.trim_end_matches(|c: char| c.is_ascii_digit());
+        let is_reference = letters.len() < name.len()
+            && !letters.is_empty()
+            && letters.bytes().all(|c| c.is_ascii_alphabetic());
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !is_reference
+            && !name.eq_ignore_ascii_case("true")
+            && !name.eq_ignore_ascii_case("false")
+    }
+
+    pub fn get(&self, name: &str) -> Option<NameTarget> {
+        self.targets.get(&name.to_lowercase()).copied()
+    }
+
+    /// Adds a name or changes the target of an existing one.
+    pub fn define(&mut self, name: &str, target: NameTarget) -> Result<(), NameError> {
+        if !Names::is_valid(name) {
+            return Err(NameError::Invalid(name.to_string()));
+        }
+        self.targets.insert(name.to_lowercase(), target);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<NameTarget> {
+        self.targets.remove(&name.to_lowercase())
+    }
+
+    /// Gives the target of `old` to `new` without changing any formula.
+    pub fn rename(&mut self, old: &str, new: &str) -> Result<(), NameError> {
+        if !Names::is_valid(new) {
+            return Err(NameError::Invalid(new.to_string()));
+        }
+        if !old.eq_ignore_ascii_case(new) && self.get(new).is_some() {
+            return Err(NameError::Exists(new.to_lowercase()));
+        }
+        let target = self
+            .remove(old)
+            .ok_or_else(|| NameError::Unknown(old.to_lowercase()))?;
+        self.targets.insert(new.to_lowercase(), target);
+        Ok(())
+    }
+
+    /// Names in alphabetical order with their targets.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, NameTarget)> {
+        self.targets
+            .iter()
+            .map(|(name, &target)| (name.as_str(), target))
+    }
+
+    pub fn clear(&mut self) {
+        self.targets.clear();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameError {
+    Invalid(String),
+    Exists(String),
+    Unknown(String),
+}
+
+impl Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameError::Invalid(name) => write!(
+                f,
+                "`{}` is not a valid name, names consist of letters, digits and `_` and must not \
+                 look like a reference",
+                name
+            ),
+            NameError::Exists(name) => write!(f, "the name `{}` already exists", name),
+            NameError::Unknown(name) => write!(f, "there is no name `{}`", name),
+        }
+    }
+}
+
+impl std::error::Error for NameError {}
@@ -0,0 +1,1212 @@
+//! Spreadsheet engine of the Cells task: cells with their inputs, computed values and formats,
+//! names, history, sorting and filtering, without any user interface. It is used by the
+//! `cells` task, but runs just as well in tests and batch jobs:
+//!
+//! ```
+//! use guis::cells::{sheet::Sheet, value::CellValue};
+//!
+//! let mut sheet = Sheet::new();
+//! sheet.set("A0".parse().unwrap(), "20");
+//! sheet.set("A1".parse().unwrap(), "=A0 * 2 + 2");
+//! assert_eq!(*sheet.value("A1".parse().unwrap()), CellValue::Number(42.0));
+//! ```
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt::{self, Display},
+    ops::RangeInclusive,
+    rc::Rc,
+    str::FromStr,
+};
+
+use super::{
+    csv::{self, CsvError},
+    document::{CellDocument, Document},
+    filter::AutoFilter,
+    format::{Align, CellFormat, NumberFormat},
+    formula::{self, BinaryOp, CellRef, Expr, ExprKind, Formula, ParseError, Reference, UnaryOp},
+    functions::{Argument, FunctionRegistry},
+    graph::{DependencyGraph, Recalculation},
+    history::{CellsHistory, Change, Edit, FormatEdit, Layout, LayoutEdit},
+    names::{NameError, NameTarget, Names},
+    sort::{self, SortKey},
+    structure::StructureChange,
+    value::{self, CellValue, ErrorKind},
+};
+
+/// Columns `A` to `ZZ`.
+pub const COLUMNS: usize = 26 * 27;
+pub const ROWS: usize = 10_000;
+
+/// Grid of cells with everything needed to compute their values, but without a user interface.
+#[derive(Default)]
+pub struct Sheet {
+    /// Cells with any input or formatting. All other cells are empty.
+    cells: HashMap<Coord, Cell>,
+    graph: DependencyGraph,
+    /// Circular reference of every cell in a cycle, as a closed path shared by all cells on it
+    /// and the position of the cell on the path.
+    cycles: HashMap<Coord, (Rc<[Coord]>, usize)>,
+    functions: FunctionRegistry,
+    names: Names,
+    history: CellsHistory,
+    filter: Option<AutoFilter>,
+}
+
+impl Sheet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn cell(&self, coord: Coord) -> &Cell {
+        self.cells.get(&coord).unwrap_or(&EMPTY_CELL)
+    }
+
+    fn cell_mut(&mut self, coord: Coord) -> &mut Cell {
+        self.cells.entry(coord).or_default()
+    }
+
+    /// Forgets the cell if it has neither input nor formatting, so that only used cells are
+    /// stored.
+    fn remove_if_empty(&mut self, coord: Coord) {
+        if self
+            .cells
+            .get(&coord)
+            .is_some_and(|cell| cell.input.is_empty() && cell.format.is_default())
+        {
+            self.cells.remove(&coord);
+        }
+    }
+
+    /// Computed value of the cell, which is up to date after every change.
+    pub fn value(&self, coord: Coord) -> &CellValue {
+        &self.cell(coord).value
+    }
+
+    /// Text the cell was given, e.g. `=A1 + 1`.
+    pub fn input(&self, coord: Coord) -> &str {
+        &self.cell(coord).input
+    }
+
+    pub fn format(&self, coord: Coord) -> &CellFormat {
+        &self.cell(coord).format
+    }
+
+    /// Changes how the cell is displayed as one step of the history.
+    pub fn set_format(&mut self, coord: Coord, format: CellFormat) {
+        self.set_formats(Some((coord, format)));
+    }
+
+    /// Changes how several cells are displayed as one step of the history. Only cells whose
+    /// format changes are stored.
+    pub fn set_formats(&mut self, formats: impl IntoIterator<Item = (Coord, CellFormat)>) {
+        self.change(Vec::new(), formats, None);
+    }
+
+    /// Problems found while parsing the formula of the cell.
+    pub fn errors(&self, coord: Coord) -> &[ParseError] {
+        self.cell(coord).content.errors()
+    }
+
+    /// Coordinates of all cells with input, row by row.
+    pub fn used_coords(&self) -> Vec<Coord> {
+        let mut coords: Vec<Coord> = self
+            .cells
+            .iter()
+            .filter(|(_, cell)| !cell.input.is_empty())
+            .map(|(&coord, _)| coord)
+            .collect();
+        coords.sort_by_key(|coord| (coord.row, coord.column));
+        coords
+    }
+
+    /// Replaces the input of a cell as one step of the history and updates every cell
+    /// depending on it.
+    pub fn set(&mut self, coord: Coord, text: impl Into<String>) {
+        self.set_inputs(Some((coord, text.into())));
+    }
+
+    /// Replaces the input of several cells as one step of the history.
+    pub fn set_inputs(&mut self, inputs: impl IntoIterator<Item = (Coord, String)>) {
+        self.change(inputs, Vec::new(), None);
+    }
+
+    /// Replaces the inputs and formats of several cells, and the names and auto-filter if a new
+    /// layout is given, as one step of the history.
+    fn change(
+        &mut self,
+        inputs: impl IntoIterator<Item = (Coord, String)>,
+        formats: impl IntoIterator<Item = (Coord, CellFormat)>,
+        layout: Option<Layout>,
+    ) {
+        let edits: Vec<Edit> = inputs
+            .into_iter()
+            .map(|(coord, after)| Edit {
+                coord,
+                before: self.cell(coord).input.clone(),
+                after,
+            })
+            .filter(|edit| edit.before != edit.after)
+            .collect();
+        let formats: Vec<FormatEdit> = formats
+            .into_iter()
+            .map(|(coord, after)| FormatEdit {
+                coord,
+                before: self.cell(coord).format.clone(),
+                after,
+            })
+            .filter(|format| format.before != format.after)
+            .collect();
+        let layout = layout
+            .map(|after| LayoutEdit {
+                before: Layout {
+                    names: self.names.clone(),
+                    filter: self.filter.clone(),
+                },
+                after,
+            })
+            .filter(|layout| layout.before != layout.after);
+        if edits.is_empty() && formats.is_empty() && layout.is_none() {
+            return;
+        }
+        let change = Change {
+            edits,
+            formats,
+            layout,
+        };
+        self.apply_change(&change);
+        self.history.push(change);
+    }
+
+    /// Gives the cells, names and auto-filter the state after the change.
+    fn apply_change(&mut self, change: &Change) {
+        for format in &change.formats {
+            self.cell_mut(format.coord).format = format.after.clone();
+            self.remove_if_empty(format.coord);
+        }
+        // Formulas using names that were added, moved or removed are updated after the inputs,
+        // which are already parsed with the new names.
+        let mut names = Vec::new();
+        if let Some(layout) = &change.layout {
+            let (before, after) = (&layout.before.names, &layout.after.names);
+            names = before
+                .iter()
+                .chain(after.iter())
+                .map(|(name, _)| name.to_string())
+                .filter(|name| before.get(name) != after.get(name))
+                .collect();
+            names.sort();
+            names.dedup();
+            self.names = layout.after.names.clone();
+            self.filter = layout.after.filter.clone();
+        }
+        self.apply_inputs(
+            change
+                .edits
+                .iter()
+                .map(|edit| (edit.coord, edit.after.clone())),
+        );
+        if !names.is_empty() {
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            self.update_name_dependents(&names);
+        }
+    }
+
+    /// Replaces the input of several cells and recalculates them afterwards at once.
+    fn apply_inputs(&mut self, inputs: impl IntoIterator<Item = (Coord, String)>) {
+        let mut changed = Vec::new();
+        for (coord, text) in inputs {
+            let content = Content::from_input(&text);
+            self.graph
+                .set_precedents(coord, content.precedents(&self.names));
+            self.graph.set_names(coord, content.names());
+            let cell = self.cell_mut(coord);
+            cell.content = content;
+            cell.input = text;
+            self.remove_if_empty(coord);
+            changed.push(coord);
+        }
+        self.recalculate(changed);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.is_full()
+    }
+
+    /// Reverts the last change and returns the top left and bottom right corner of the cells it
+    /// affected, if it affected any cells instead of only names or the auto-filter.
+    pub fn undo(&mut self) -> Option<(Coord, Coord)> {
+        let change = self.history.undo()?.reversed();
+        self.apply_change(&change);
+        edited_area(&change)
+    }
+
+    /// Applies the last undone change again and returns the top left and bottom right corner of
+    /// the cells it affected, if any.
+    pub fn redo(&mut self) -> Option<(Coord, Coord)> {
+        let change = self.history.redo()?.clone();
+        self.apply_change(&change);
+        edited_area(&change)
+    }
+
+    /// Removes the input of all cells between two corners.
+    pub fn clear(&mut self, start: Coord, end: Coord) {
+        let (top_left, bottom_right) = corners(start, end);
+        let inputs: Vec<(Coord, String)> = self
+            .used_coords()
+            .into_iter()
+            .filter(|coord| {
+                (top_left.column..=bottom_right.column).contains(&coord.column)
+                    && (top_left.row..=bottom_right.row).contains(&coord.row)
+            })
+            .map(|coord| (coord, String::new()))
+            .collect();
+        self.set_inputs(inputs);
+    }
+
+    /// Extends the first cells of every column or row of the area between two corners over the
+    /// rest of it. Numbers that form a series like `1, 2, 3` are continued, while everything
+    /// else is repeated with the references of formulas moved along. An area of a single row or
+    /// column is filled with the cell before it.
+    pub fn fill(&mut self, start: Coord, end: Coord, fill: Fill) {
+        let (top_left, bottom_right) = corners(start, end);
+        // Lines are the columns when filling down and the rows when filling right.
+        let (lines, length) = match fill {
+            Fill::Down => (
+                top_left.column..=bottom_right.column,
+                bottom_right.row - top_left.row + 1,
+            ),
+            Fill::Right => (
+                top_left.row..=bottom_right.row,
+                bottom_right.column - top_left.column + 1,
+            ),
+        };
+        let coord = |line: usize, position: isize| match fill {
+            Fill::Down => Coord {
+                column: line,
+                row: top_left.row,
+            }
+            .offset(0, position),
+            Fill::Right => Coord {
+                column: top_left.column,
+                row: line,
+            }
+            .offset(position, 0),
+        };
+        let positions = if length == 1 {
+            -1..1
+        } else {
+            0..length as isize
+        };
+
+        let mut inputs = Vec::new();
+        for line in lines {
+            let cells: Vec<Coord> = positions
+                .clone()
+                .filter_map(|position| coord(line, position))
+                .collect();
+            let mut seed = cells
+                .iter()
+                .take_while(|&&coord| !self.cell(coord).input.is_empty())
+                .count();
+            if seed == 0 {
+                continue;
+            }
+            if seed == cells.len() {
+                seed = 1;
+            }
+
+            let seed_inputs: Vec<&str> = cells[..seed]
+                .iter()
+                .map(|&coord| self.cell(coord).input.as_str())
+                .collect();
+            if let Some(series) = number_series(&seed_inputs) {
+                for (index, &target) in cells.iter().enumerate().skip(seed) {
+                    inputs.push((target, series(index)));
+                }
+                continue;
+            }
+            for (index, &target) in cells.iter().enumerate().skip(seed) {
+                let source = cells[index % seed];
+                let input = &self.cell(source).input;
+                let input = if input.starts_with('=') {
+                    let columns = target.column as isize - source.column as isize;
+                    let rows = target.row as isize - source.row as isize;
+                    formula::adjust_references(input, |reference| reference.offset(columns, rows))
+                } else {
+                    input.clone()
+                };
+                inputs.push((target, input));
+            }
+        }
+        self.set_inputs(inputs);
+    }
+
+    /// Inputs of the cells between two corners, row by row.
+    pub fn inputs(&self, start: Coord, end: Coord) -> Vec<Vec<String>> {
+        let (top_left, bottom_right) = corners(start, end);
+        (top_left.row..=bottom_right.row)
+            .map(|row| {
+                (top_left.column..=bottom_right.column)
+                    .map(|column| self.cell(Coord { column, row }).input.clone())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Puts rows of inputs that were taken from `origin` at `target`, where references in
+    /// formulas move along with the cells, unless they are absolute. Inputs outside of the grid
+    /// are dropped. Returns the first and the last pasted cell.
+    pub fn paste(
+        &mut self,
+        inputs: &[Vec<String>],
+        origin: Coord,
+        target: Coord,
+    ) -> Option<(Coord, Coord)> {
+        let columns = target.column as isize - origin.column as isize;
+        let rows = target.row as isize - origin.row as isize;
+        let inputs: Vec<(Coord, String)> = grid_inputs(inputs, target)
+            .map(|(coord, input)| {
+                let input = if input.starts_with('=') && (columns, rows) != (0, 0) {
+                    formula::adjust_references(input, |reference| reference.offset(columns, rows))
+                } else {
+                    input.to_string()
+                };
+                (coord, input)
+            })
+            .collect();
+        let pasted = match (inputs.first(), inputs.last()) {
+            (Some((first, _)), Some((last, _))) => Some((*first, *last)),
+            _ => None,
+        };
+        self.set_inputs(inputs);
+        pasted
+    }
+
+    /// Fills the grid with the fields of a CSV file, where the first field of the first record
+    /// ends up in `anchor`. Fields outside of the grid are dropped.
+    pub fn import_csv(&mut self, input: &str, anchor: Coord) -> Result<(), CsvError> {
+        let records = csv::parse(input)?;
+        let inputs = records.into_iter().enumerate().flat_map(|(row, record)| {
+            record
+                .into_iter()
+                .enumerate()
+                .map(move |(column, field)| {
+                    let coord = Coord {
+                        column: anchor.column + column,
+                        row: anchor.row + row,
+                    };
+                    (coord, field)
+                })
+                .filter(|(coord, _)| coord.column < COLUMNS && coord.row < ROWS)
+        });
+        self.set_inputs(inputs);
+        Ok(())
+    }
+
+    /// Writes the sheet with formulas, formats and names as a [`Document`].
+    pub fn to_document(&self) -> Document {
+        let mut document = Document::new();
+        for (name, target) in self.names.iter() {
+            document.names.insert(name.to_string(), target);
+        }
+        let mut coords: Vec<Coord> = self.cells.keys().copied().collect();
+        coords.sort_by_key(|coord| (coord.row, coord.column));
+        for coord in coords {
+            let cell = self.cell(coord);
+            document.cells.push(CellDocument {
+                coord,
+                input: cell.input.clone(),
+                format: cell.format.clone(),
+            });
+        }
+        document
+    }
+
+    /// Replaces the whole sheet with the document, which starts a new history. Invalid names are
+    /// ignored, and so are column widths, which are up to the user interface.
+    pub fn load_document(&mut self, document: Document) {
+        self.names.clear();
+        for (name, target) in document.names {
+            self.names.define(&name, target).ok();
+        }
+
+        let mut inputs: HashMap<Coord, String> = self
+            .used_coords()
+            .into_iter()
+            .map(|coord| (coord, String::new()))
+            .collect();
+        for cell in self.cells.values_mut() {
+            cell.format = CellFormat::default();
+        }
+        self.cells.retain(|_, cell| !cell.input.is_empty());
+        for cell in document.cells {
+            self.cell_mut(cell.coord).format = cell.format;
+            inputs.insert(cell.coord, cell.input);
+        }
+        self.filter = None;
+        self.apply_inputs(inputs);
+        self.history.clear();
+    }
+
+    /// Writes the grid from `A0` to the last cell with any input as CSV.
+    pub fn export_csv(&self, export: CsvExport) -> String {
+        let (mut columns, mut rows) = (0, 0);
+        for coord in self.used_coords() {
+            columns = columns.max(coord.column + 1);
+            rows = rows.max(coord.row + 1);
+        }
+
+        csv::write((0..rows).map(|row| {
+            (0..columns).map(move |column| {
+                let cell = self.cell(Coord { column, row });
+                match export {
+                    CsvExport::Formulas => cell.input.clone(),
+                    CsvExport::Values => cell.value.to_string(),
+                }
+            })
+        }))
+    }
+
+    /// Makes a function available to formulas and updates all cells, because a formula might
+    /// have called the function before it existed.
+    pub fn register_function(
+        &mut self,
+        name: &str,
+        function: impl Fn(&[Argument]) -> CellValue + 'static,
+    ) {
+        self.functions.register(name, function);
+        let formulas: Vec<Coord> = self
+            .cells
+            .iter()
+            .filter(|(_, cell)| matches!(cell.content, Content::Formula(_)))
+            .map(|(&coord, _)| coord)
+            .collect();
+        self.recalculate(formulas);
+    }
+
+    /// Evaluates `coords` and every cell depending on them, so that each cell only reads values
+    /// that are already up to date.
+    fn recalculate(&mut self, coords: impl IntoIterator<Item = Coord>) {
+        for step in self.graph.recalculation_order(coords) {
+            match step {
+                Recalculation::Cell(coord) => {
+                    self.cycles.remove(&coord);
+                    let value = self.cell(coord).content.evaluate(
+                        &self.cells,
+                        &self.functions,
+                        &self.names,
+                    );
+                    // Empty cells always evaluate to an empty value and are not stored.
+                    if let Some(cell) = self.cells.get_mut(&coord) {
+                        cell.value = value;
+                    }
+                }
+                Recalculation::Cycle(cells) => {
+                    // Searching a path for every cell would take quadratic time in long cycles,
+                    // so every path found is shared by all cells on it.
+                    let members: HashSet<Coord> = cells.iter().copied().collect();
+                    let mut found = HashSet::new();
+                    for coord in cells {
+                        if let Some(cell) = self.cells.get_mut(&coord) {
+                            cell.value = ErrorKind::Cycle.into();
+                        }
+                        if found.contains(&coord) {
+                            continue;
+                        }
+                        if let Some(path) = self.graph.cycle_path(coord, &members) {
+                            let path: Rc<[Coord]> = path.into();
+                            for (position, &cell) in path[..path.len() - 1].iter().enumerate() {
+                                if found.insert(cell) {
+                                    self.cycles.insert(cell, (Rc::clone(&path), position));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sorts the rows between two corners by `keys`, leaving the first row in place if it is a
+    /// header. Formulas move with their rows and keep referring to the same cells, while
+    /// references outside of the area stay as they are. The sort is one step of the history,
+    /// including the moved formats.
+    pub fn sort(&mut self, start: Coord, end: Coord, keys: &[SortKey], has_header: bool) {
+        let (top_left, bottom_right) = corners(start, end);
+        let columns = top_left.column..=bottom_right.column;
+        let keys: Vec<SortKey> = keys
+            .iter()
+            .copied()
+            .filter(|key| columns.contains(&key.column))
+            .collect();
+        let first_row = top_left.row + usize::from(has_header);
+        if keys.is_empty() || first_row >= bottom_right.row {
+            return;
+        }
+        let mut rows: Vec<usize> = (first_row..=bottom_right.row).collect();
+        rows.sort_by(|&lhs, &rhs| {
+            keys.iter()
+                .map(|key| {
+                    let value = |row| {
+                        &self
+                            .cell(Coord {
+                                column: key.column,
+                                row,
+                            })
+                            .value
+                    };
+                    sort::compare(value(lhs), value(rhs), key.descending)
+                })
+                .find(|&ordering| ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let new_rows: HashMap<usize, usize> = rows.iter().copied().zip(first_row..).collect();
+        let move_ref = |reference: CellRef| match new_rows.get(&reference.coord.row) {
+            Some(&row) if columns.contains(&reference.coord.column) => CellRef {
+                coord: Coord {
+                    row,
+                    ..reference.coord
+                },
+                ..reference
+            },
+            _ => reference,
+        };
+        // Ranges only move if they are part of a single row, e.g. `A1:C1` in `D1`.
+        let move_reference = |reference: Reference| {
+            Some(match reference {
+                Reference::Cell(reference) => Reference::Cell(move_ref(reference)),
+                Reference::Range(start, end)
+                    if start.coord.row == end.coord.row
+                        && columns.contains(&start.coord.column)
+                        && columns.contains(&end.coord.column) =>
+                {
+                    Reference::Range(move_ref(start), move_ref(end))
+                }
+                reference => reference,
+            })
+        };
+
+        let mut inputs = Vec::new();
+        let mut formats = Vec::new();
+        for (&old_row, row) in rows.iter().zip(first_row..) {
+            for column in columns.clone() {
+                let cell = self.cell(Coord {
+                    column,
+                    row: old_row,
+                });
+                let input = if cell.input.starts_with('=') {
+                    formula::adjust_references(&cell.input, move_reference)
+                } else {
+                    cell.input.clone()
+                };
+                inputs.push((Coord { column, row }, input));
+                formats.push((Coord { column, row }, cell.format.clone()));
+            }
+        }
+        self.change(inputs, formats, None);
+    }
+
+    pub fn filter(&self) -> Option<&AutoFilter> {
+        self.filter.as_ref()
+    }
+
+    /// Replaces the auto-filter as one step of the history.
+    pub fn set_filter(&mut self, filter: Option<AutoFilter>) {
+        let names = self.names.clone();
+        self.change(Vec::new(), Vec::new(), Some(Layout { names, filter }));
+    }
+
+    /// Rows hidden by the auto-filter.
+    pub fn hidden_rows(&self) -> BTreeSet<usize> {
+        match &self.filter {
+            Some(filter) => (filter.start().row..=filter.end().row)
+                .filter(|&row| filter.is_hidden(row, |coord| &self.cell(coord).value))
+                .collect(),
+            None => BTreeSet::new(),
+        }
+    }
+
+    /// Inserts or deletes rows or columns as one step of the history. Formulas are rewritten to
+    /// keep referring to the same cells, where references to deleted cells become `#REF` errors,
+    /// and names referring only to deleted cells are removed.
+    pub fn change_structure(&mut self, change: StructureChange) {
+        let mut names = Names::default();
+        for (name, target) in self.names.iter() {
+            if let Some(target) = change.move_target(target) {
+                // Names that are already defined are valid.
+                let _ = names.define(name, target);
+            }
+        }
+        let filter = self
+            .filter
+            .as_ref()
+            .and_then(|filter| filter.moved(&change));
+
+        // All moved cells are cleared first, so that they are only replaced by moved cells.
+        let mut inputs: HashMap<Coord, String> = HashMap::new();
+        let mut formats: HashMap<Coord, CellFormat> = HashMap::new();
+        for (&coord, cell) in &self.cells {
+            inputs.entry(coord).or_default();
+            formats.entry(coord).or_default();
+            if let Some(new_coord) = change.move_coord(coord) {
+                let input = if cell.input.starts_with('=') {
+                    formula::adjust_references(&cell.input, |reference| {
+                        change.move_reference(reference)
+                    })
+                } else {
+                    cell.input.clone()
+                };
+                inputs.insert(new_coord, input);
+                formats.insert(new_coord, cell.format.clone());
+            }
+        }
+        self.change(inputs, formats, Some(Layout { names, filter }));
+    }
+
+    /// Names in alphabetical order with their targets.
+    pub fn names(&self) -> impl Iterator<Item = (&str, NameTarget)> {
+        self.names.iter()
+    }
+
+    /// Adds a name or changes its target as one step of the history, and updates the formulas
+    /// using it.
+    pub fn define_name(&mut self, name: &str, target: NameTarget) -> Result<(), NameError> {
+        let mut names = self.names.clone();
+        names.define(name, target)?;
+        self.set_names(names);
+        Ok(())
+    }
+
+    /// Removes a name as one step of the history, which turns the formulas using it into
+    /// `#NAME` errors.
+    pub fn remove_name(&mut self, name: &str) -> Option<NameTarget> {
+        let mut names = self.names.clone();
+        let target = names.remove(name)?;
+        self.set_names(names);
+        Some(target)
+    }
+
+    /// Renames a name as one step of the history without changing any formula, so formulas
+    /// using the old name turn into `#NAME` errors while formulas already using the new name
+    /// start working.
+    pub fn rename_name(&mut self, old: &str, new: &str) -> Result<(), NameError> {
+        let mut names = self.names.clone();
+        names.rename(old, new)?;
+        self.set_names(names);
+        Ok(())
+    }
+
+    fn set_names(&mut self, names: Names) {
+        let filter = self.filter.clone();
+        self.change(Vec::new(), Vec::new(), Some(Layout { names, filter }));
+    }
+
+    fn update_name_dependents(&mut self, names: &[&str]) {
+        let mut coords: Vec<Coord> = names
+            .iter()
+            .flat_map(|name| self.graph.name_dependents(&name.to_lowercase()))
+            .collect();
+        coords.sort_by_key(|coord| (coord.row, coord.column));
+        coords.dedup();
+        for &coord in &coords {
+            let precedents = self.cell(coord).content.precedents(&self.names);
+            self.graph.set_precedents(coord, precedents);
+        }
+        self.recalculate(coords);
+    }
+
+    pub fn is_in_cycle(&self, coord: Coord) -> bool {
+        self.cycles.contains_key(&coord)
+    }
+
+    /// Circular reference the cell is part of, starting and ending with the cell itself.
+    pub fn cycle(&self, coord: Coord) -> Option<Vec<Coord>> {
+        let (path, position) = self.cycles.get(&coord)?;
+        // The path is closed, so its last cell is the first one again.
+        let cells = &path[..path.len() - 1];
+        let (before, after) = cells.split_at(*position);
+        Some(
+            after
+                .iter()
+                .chain(before)
+                .chain(&after[..1])
+                .copied()
+                .collect(),
+        )
+    }
+
+    pub fn dependency_graph(&self) -> &DependencyGraph {
+        &self.graph
+    }
+
+    /// Areas read by a formula that has not been entered yet, including the targets of the names
+    /// it uses.
+    pub fn referenced_areas(&self, input: &str) -> Vec<Area> {
+        Content::from_input(input).precedents(&self.names)
+    }
+
+    /// Functions and names starting with `prefix`, ignoring case, in alphabetical order.
+    /// Functions are followed by `(`.
+    pub fn completions(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.to_lowercase();
+        let functions = self.functions.names().map(|name| format!("{}(", name));
+        let names = self.names.iter().map(|(name, _)| name.to_string());
+        let mut completions: Vec<String> = functions
+            .chain(names)
+            .filter(|completion| completion.starts_with(&prefix))
+            .collect();
+        completions.sort();
+        completions
+    }
+}
+
+/// Top left and bottom right corner of the area between two corners.
+fn corners(start: Coord, end: Coord) -> (Coord, Coord) {
+    (
+        Coord {
+            column: start.column.min(end.column),
+            row: start.row.min(end.row),
+        },
+        Coord {
+            column: start.column.max(end.column),
+            row: start.row.max(end.row),
+        },
+    )
+}
+
+fn edited_area(change: &Change) -> Option<(Coord, Coord)> {
+    let coords = || {
+        let formats = change.formats.iter().map(|format| format.coord);
+        change.edits.iter().map(|edit| edit.coord).chain(formats)
+    };
+    Some((
+        Coord {
+            column: coords().map(|coord| coord.column).min()?,
+            row: coords().map(|coord| coord.row).min()?,
+        },
+        Coord {
+            column: coords().map(|coord| coord.column).max()?,
+            row: coords().map(|coord| coord.row).max()?,
+        },
+    ))
+}
+
+/// Continuation of at least two numbers with a constant difference, which returns the input of
+/// the number at an index and has as many decimals as the given numbers.
+fn number_series(inputs: &[&str]) -> Option<impl Fn(usize) -> String> {
+    if inputs.len() < 2 {
+        return None;
+    }
+    let mut numbers = Vec::new();
+    for input in inputs {
+        match Content::from_input(input) {
+            Content::Number(number) => numbers.push(number),
+            _ => return None,
+        }
+    }
+    let (first, step) = (numbers[0], numbers[1] - numbers[0]);
+    let is_series = numbers.windows(2).all(|pair| {
+        let difference = pair[1] - pair[0];
+        (difference - step).abs() <= f64::EPSILON * difference.abs().max(step.abs()) * 4.0
+    });
+    if !is_series {
+        return None;
+    }
+
+    let decimals = inputs
+        .iter()
+        .map(|input| {
+            let input = input.trim();
+            if input.contains(&['e', 'E'][..]) {
+                return None;
+            }
+            Some(input.find('.').map_or(0, |point| input.len() - point - 1))
+        })
+        .collect::<Option<Vec<usize>>>()
+        .and_then(|decimals| decimals.into_iter().max());
+    Some(move |index: usize| {
+        let number = first + step * index as f64;
+        match decimals {
+            Some(decimals) => format!("{:.*}", decimals, number),
+            None => number.to_string(),
+        }
+    })
+}
+
+/// Rows of inputs placed with their first field at `target`, without the fields outside of the
+/// grid.
+fn grid_inputs(rows: &[Vec<String>], target: Coord) -> impl Iterator<Item = (Coord, &str)> {
+    rows.iter().enumerate().flat_map(move |(row, inputs)| {
+        inputs
+            .iter()
+            .enumerate()
+            .filter_map(move |(column, input)| {
+                let coord = target.offset(column as isize, row as isize)?;
+                Some((coord, input.as_str()))
+            })
+    })
+}
+
+/// Direction in which [`Sheet::fill`] extends the first cells of an area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fill {
+    Down,
+    Right,
+}
+
+/// What to write into an exported CSV file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvExport {
+    /// The input of every cell, so that the file can be imported again.
+    Formulas,
+    /// The computed value of every cell.
+    Values,
+}
+
+#[derive(Default)]
+struct Cell {
+    input: String,
+    content: Content,
+    value: CellValue,
+    format: CellFormat,
+}
+
+static EMPTY_CELL: Cell = Cell {
+    input: String::new(),
+    content: Content::Empty,
+    value: CellValue::Empty,
+    format: CellFormat {
+        align: Align::Auto,
+        number: NumberFormat::General,
+    },
+};
+
+/// Position of a cell in the grid, written as column letter followed by row number, e.g. `B12`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Coord {
+    pub column: usize,
+    pub row: usize,
+}
+
+/// Rectangular area of cells between two corners, both included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Area {
+    /// Top left corner.
+    pub start: Coord,
+    /// Bottom right corner.
+    pub end: Coord,
+}
+
+impl Area {
+    /// Area between two opposite corners in any order.
+    pub fn new(corner: Coord, other_corner: Coord) -> Self {
+        let (start, end) = corners(corner, other_corner);
+        Self { start, end }
+    }
+
+    /// Area of a single cell.
+    pub fn cell(coord: Coord) -> Self {
+        Self {
+            start: coord,
+            end: coord,
+        }
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        (self.start.column..=self.end.column).contains(&coord.column)
+            && (self.start.row..=self.end.row).contains(&coord.row)
+    }
+
+    pub fn columns(&self) -> RangeInclusive<usize> {
+        self.start.column..=self.end.column
+    }
+
+    /// Number of cells in the area.
+    pub fn cell_count(&self) -> usize {
+        (self.end.column - self.start.column + 1) * (self.end.row - self.start.row + 1)
+    }
+
+    /// Cells of the area, row by row.
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        Coord::range(self.start, self.end)
+    }
+}
+
+impl Coord {
+    /// Cells of the rectangular area between two corners, row by row.
+    pub fn range(start: Coord, end: Coord) -> impl Iterator<Item = Coord> {
+        let columns = start.column.min(end.column)..=start.column.max(end.column);
+        (start.row.min(end.row)..=start.row.max(end.row))
+            .flat_map(move |row| columns.clone().map(move |column| Coord { column, row }))
+    }
+
+    /// Name of a column, counting `A` to `Z` and continuing with `AA`, `AB` and so on.
+    pub fn column_name(column: usize) -> String {
+        let mut letters = Vec::new();
+        let mut rest = column + 1;
+        while rest > 0 {
+            rest -= 1;
+            letters.push(char::from(b'A' + (rest % 26) as u8));
+            rest /= 26;
+        }
+        letters.iter().rev().collect()
+    }
+
+    /// Index of a column name like `B` or `AB`, ignoring case.
+    pub fn parse_column(name: &str) -> Option<usize> {
+        if name.is_empty() || name.len() > 2 || !name.bytes().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        let column = name.bytes().fold(0, |column, c| {
+            column * 26 + usize::from(c.to_ascii_uppercase() - b'A') + 1
+        }) - 1;
+        Some(column).filter(|&column| column < COLUMNS)
+    }
+
+    /// Coordinate from a column name and a row number, if both are inside of the grid.
+    pub(super) fn from_parts(column: &str, row: &str) -> Option<Coord> {
+        let column = Coord::parse_column(column)?;
+        if row.is_empty() || !row.bytes().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let row = row.parse::<usize>().ok().filter(|&row| row < ROWS)?;
+        Some(Coord { column, row })
+    }
+
+    /// Coordinate moved by the given number of columns and rows, if it is still inside of the
+    /// grid.
+    pub fn offset(self, columns: isize, rows: isize) -> Option<Coord> {
+        let column = (self.column as isize).checked_add(columns)?;
+        let row = (self.row as isize).checked_add(rows)?;
+        if (0..COLUMNS as isize).contains(&column) && (0..ROWS as isize).contains(&row) {
+            Some(Coord {
+                column: column as usize,
+                row: row as usize,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for Coord {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(s.len());
+        let (column, row) = s.split_at(split);
+        Coord::from_parts(column, row).ok_or(())
+    }
+}
+
+impl Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", Coord::column_name(self.column), self.row)
+    }
+}
+
+/// Parsed input of a cell: either a literal or a formula starting with `=`.
+#[derive(Debug, Clone, Default)]
+enum Content {
+    #[default]
+    Empty,
+    Number(f64),
+    Text(String),
+    Boolean(bool),
+    Formula(Formula),
+}
+
+impl Content {
+    fn from_input(text: &str) -> Self {
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            Content::Empty
+        } else if text.starts_with('=') {
+            Content::Formula(formula::parse(text))
+        } else if let Some(number) = value::parse_number(trimmed) {
+            Content::Number(number)
+        } else if trimmed.eq_ignore_ascii_case("true") {
+            Content::Boolean(true)
+        } else if trimmed.eq_ignore_ascii_case("false") {
+            Content::Boolean(false)
+        } else {
+            Content::Text(text.to_string())
+        }
+    }
+
+    fn evaluate(
+        &self,
+        cells: &HashMap<Coord, Cell>,
+        functions: &FunctionRegistry,
+        names: &Names,
+    ) -> CellValue {
+        match self {
+            Content::Empty => CellValue::Empty,
+            Content::Number(number) => CellValue::Number(*number),
+            Content::Text(text) => CellValue::Text(text.clone()),
+            Content::Boolean(boolean) => CellValue::Boolean(*boolean),
+            Content::Formula(formula) if formula.is_valid() => {
+                evaluate(&formula.expr, cells, functions, names)
+            }
+            Content::Formula(_) => ErrorKind::Parse.into(),
+        }
+    }
+
+    /// Areas read by the formula, including the targets of the names it uses.
+    fn precedents(&self, names: &Names) -> Vec<Area> {
+        match self {
+            Content::Formula(formula) => {
+                let mut precedents = formula.expr.precedents();
+                for name in formula.expr.names() {
+                    precedents.extend(names.get(&name).map(|target| target.area()));
+                }
+                precedents
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn names(&self) -> Vec<String> {
+        match self {
+            Content::Formula(formula) => formula.expr.names(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn errors(&self) -> &[ParseError] {
+        match self {
+            Content::Formula(formula) => &formula.errors,
+            _ => &[],
+        }
+    }
+}
+
+fn evaluate(
+    expr: &Expr,
+    cells: &HashMap<Coord, Cell>,
+    functions: &FunctionRegistry,
+    names: &Names,
+) -> CellValue {
+    let result = match &expr.kind {
+        ExprKind::Number(number) => return CellValue::number(*number),
+        ExprKind::Text(text) => return CellValue::Text(text.clone()),
+        ExprKind::Boolean(boolean) => return CellValue::Boolean(*boolean),
+        ExprKind::Ref(reference) => return value(cells, reference.coord),
+        ExprKind::Unary(op, operand) => {
+            evaluate_unary(*op, evaluate(operand, cells, functions, names))
+        }
+        ExprKind::Binary(op, lhs, rhs) => {
+            let lhs = evaluate(lhs, cells, functions, names);
+            let rhs = evaluate(rhs, cells, functions, names);
+            evaluate_binary(*op, &lhs, &rhs)
+        }
+        ExprKind::Call(name, args) => {
+            let function = match functions.get(name) {
+                Some(function) => function,
+                None => return ErrorKind::Name.into(),
+            };
+            let args: Vec<Argument> = args
+                .iter()
+                .map(|arg| match &arg.kind {
+                    ExprKind::Range(start, end) => {
+                        Argument::Range(range_values(cells, Area::new(start.coord, end.coord)))
+                    }
+                    ExprKind::Name(name) => match names.get(name) {
+                        Some(NameTarget::Range(start, end)) => {
+                            Argument::Range(range_values(cells, Area::new(start, end)))
+                        }
+                        _ => Argument::Value(evaluate(arg, cells, functions, names)),
+                    },
+                    _ => Argument::Value(evaluate(arg, cells, functions, names)),
+                })
+                .collect();
+            return function(&args);
+        }
+        // Ranges are only meaningful as function arguments.
+        ExprKind::Range(..) => Err(ErrorKind::Value),
+        ExprKind::Name(name) => match names.get(name) {
+            Some(NameTarget::Cell(coord)) => return value(cells, coord),
+            Some(NameTarget::Range(..)) => Err(ErrorKind::Value),
+            None => Err(ErrorKind::Name),
+        },
+        ExprKind::InvalidRef => Err(ErrorKind::Ref),
+        ExprKind::Error => Err(ErrorKind::Parse),
+    };
+    result.unwrap_or_else(CellValue::Error)
+}
+
+fn value(cells: &HashMap<Coord, Cell>, coord: Coord) -> CellValue {
+    cells
+        .get(&coord)
+        .map_or(CellValue::Empty, |cell| cell.value.clone())
+}
+
+/// Values of the cells in an area that are not empty, row by row. Only the cells inside of the
+/// area are visited, or only the stored cells if there are fewer of them, so that a range over
+/// the whole grid costs as much as the cells in use.
+fn range_values(cells: &HashMap<Coord, Cell>, area: Area) -> Vec<CellValue> {
+    let coords: Vec<Coord> = if area.cell_count() <= cells.len() {
+        area.coords()
+            .filter(|coord| cells.contains_key(coord))
+            .collect()
+    } else {
+        let mut coords: Vec<Coord> = cells
+            .keys()
+            .copied()
+            .filter(|&coord| area.contains(coord))
+            .collect();
+        coords.sort_by_key(|coord| (coord.row, coord.column));
+        coords
+    };
+    coords
+        .iter()
+        .map(|coord| &cells[coord].value)
+        .filter(|value| !matches!(value, CellValue::Empty))
+        .cloned()
+        .collect()
+}
+
+fn evaluate_unary(op: UnaryOp, operand: CellValue) -> Result<CellValue, ErrorKind> {
+    let number = operand.as_number()?;
+    Ok(match op {
+        UnaryOp::Plus => CellValue::Number(number),
+        UnaryOp::Neg => CellValue::Number(-number),
+    })
+}
+
+/// Applies an operator, where errors of the left operand take precedence.
+fn evaluate_binary(op: BinaryOp, lhs: &CellValue, rhs: &CellValue) -> Result<CellValue, ErrorKind> {
+    Ok(match op {
+        BinaryOp::Concat => {
+            let lhs = lhs.as_text()?;
+            CellValue::Text(format!("{}{}", lhs, rhs.as_text()?))
+        }
+        BinaryOp::Eq => CellValue::Boolean(lhs.compare(rhs)? == Ordering::Equal),
+        BinaryOp::Ne => CellValue::Boolean(lhs.compare(rhs)? != Ordering::Equal),
+        BinaryOp::Lt => CellValue::Boolean(lhs.compare(rhs)? == Ordering::Less),
+        BinaryOp::Le => CellValue::Boolean(lhs.compare(rhs)? != Ordering::Greater),
+        BinaryOp::Gt => CellValue::Boolean(lhs.compare(rhs)? == Ordering::Greater),
+        BinaryOp::Ge => CellValue::Boolean(lhs.compare(rhs)? != Ordering::Less),
+        BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Pow => {
+            let lhs = lhs.as_number()?;
+            let rhs = rhs.as_number()?;
+            CellValue::number(match op {
+                BinaryOp::Add => lhs + rhs,
+                BinaryOp::Sub => lhs - rhs,
+                BinaryOp::Mul => lhs * rhs,
+                BinaryOp::Div if rhs == 0.0 => return Err(ErrorKind::Div0),
+                BinaryOp::Div => lhs / rhs,
+                _ => lhs.powf(rhs),
+            })
+        }
+    })
+}
@@ -0,0 +1,46 @@
+//! Sorting the rows of a range by the values in some of its columns.
+
+use std::cmp::Ordering;
+
+use super::value::CellValue;
+
+/// Column to sort by. Earlier keys take precedence, later keys only order rows that are equal in
+/// all earlier keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub column: usize,
+    pub descending: bool,
+}
+
+/// Orders numbers before text before booleans before errors, where numbers are compared by
+/// value and text case insensitively. Empty cells always come last, also when sorting in
+/// descending order.
+pub fn compare(lhs: &CellValue, rhs: &CellValue, descending: bool) -> Ordering {
+    fn rank(value: &CellValue) -> u8 {
+        match value {
+            CellValue::Number(_) => 0,
+            CellValue::Text(_) => 1,
+            CellValue::Boolean(_) => 2,
+            CellValue::Error(_) => 3,
+            CellValue::Empty => 4,
+        }
+    }
+
+    let ordering = match (lhs, rhs) {
+        (CellValue::Empty, CellValue::Empty) => return Ordering::Equal,
+        (CellValue::Empty, _) => return Ordering::Greater,
+        (_, CellValue::Empty) => return Ordering::Less,
+        (CellValue::Number(lhs), CellValue::Number(rhs)) => {
+            lhs.partial_cmp(rhs).unwrap_or(Ordering::Equal)
+        }
+        (CellValue::Text(lhs), CellValue::Text(rhs)) => lhs.to_lowercase().cmp(&rhs.to_lowercase()),
+        (CellValue::Boolean(lhs), CellValue::Boolean(rhs)) => lhs.cmp(rhs),
+        (CellValue::Error(lhs), CellValue::Error(rhs)) => lhs.as_str().cmp(rhs.as_str()),
+        (lhs, rhs) => rank(lhs).cmp(&rank(rhs)),
+    };
+    if descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
@@ -0,0 +1,151 @@
+//! Inserting and deleting whole rows and columns, which moves cells and everything referring
+//! to them.
+
+use super::{
+    formula::{CellRef, Reference},
+    names::NameTarget,
+    Coord, COLUMNS, ROWS,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    Rows,
+    Columns,
+}
+
+impl Axis {
+    fn len(self) -> usize {
+        match self {
+            Axis::Rows => ROWS,
+            Axis::Columns => COLUMNS,
+        }
+    }
+
+    fn index(self, coord: Coord) -> usize {
+        match self {
+            Axis::Rows => coord.row,
+            Axis::Columns => coord.column,
+        }
+    }
+
+    fn with_index(self, coord: Coord, index: usize) -> Coord {
+        match self {
+            Axis::Rows => Coord {
+                row: index,
+                ..coord
+            },
+            Axis::Columns => Coord {
+                column: index,
+                ..coord
+            },
+        }
+    }
+}
+
+/// Rows or columns inserted before `at` or deleted starting with `at`.
+///
+/// Cells behind the change move, and so do references to them, regardless of being absolute.
+/// Ranges grow when rows or columns are inserted inside of them and shrink when some of their
+/// rows or columns are deleted. References to deleted cells and cells moved out of the grid are
+/// lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructureChange {
+    Insert { axis: Axis, at: usize, count: usize },
+    Delete { axis: Axis, at: usize, count: usize },
+}
+
+impl StructureChange {
+    pub fn axis(&self) -> Axis {
+        match *self {
+            StructureChange::Insert { axis, .. } | StructureChange::Delete { axis, .. } => axis,
+        }
+    }
+
+    /// New position of a cell, or `None` if it is deleted or moved out of the grid.
+    pub fn move_coord(&self, coord: Coord) -> Option<Coord> {
+        let axis = self.axis();
+        let index = self.move_index(axis.index(coord))?;
+        Some(axis.with_index(coord, index))
+    }
+
+    /// New corners of the range between `start` and `end`, or `None` if all of it is deleted or
+    /// moved out of the grid.
+    pub fn move_range(&self, start: Coord, end: Coord) -> Option<(Coord, Coord)> {
+        let axis = self.axis();
+        let (first, last) = (axis.index(start), axis.index(end));
+        let (new_first, new_last) = self.move_span(first.min(last), first.max(last))?;
+        // Keep the corners in the order they were written.
+        let (new_start, new_end) = if first <= last {
+            (new_first, new_last)
+        } else {
+            (new_last, new_first)
+        };
+        Some((
+            axis.with_index(start, new_start),
+            axis.with_index(end, new_end),
+        ))
+    }
+
+    pub fn move_reference(&self, reference: Reference) -> Option<Reference> {
+        let moved = |reference: CellRef, coord| CellRef { coord, ..reference };
+        match reference {
+            Reference::Cell(reference) => {
+                let coord = self.move_coord(reference.coord)?;
+                Some(Reference::Cell(moved(reference, coord)))
+            }
+            Reference::Range(start, end) => {
+                let (start_coord, end_coord) = self.move_range(start.coord, end.coord)?;
+                Some(Reference::Range(
+                    moved(start, start_coord),
+                    moved(end, end_coord),
+                ))
+            }
+        }
+    }
+
+    pub fn move_target(&self, target: NameTarget) -> Option<NameTarget> {
+        match target {
+            NameTarget::Cell(coord) => Some(NameTarget::Cell(self.move_coord(coord)?)),
+            NameTarget::Range(start, end) => {
+                let (start, end) = self.move_range(start, end)?;
+                Some(NameTarget::Range(start, end))
+            }
+        }
+    }
+
+    fn move_index(&self, index: usize) -> Option<usize> {
+        match *self {
+            _ if index < self.at() => Some(index),
+            StructureChange::Insert { axis, count, .. } => {
+                Some(index + count).filter(|&index| index < axis.len())
+            }
+            StructureChange::Delete { at, count, .. } if index < at + count => None,
+            StructureChange::Delete { count, .. } => Some(index - count),
+        }
+    }
+
+    /// New first and last index of the rows or columns between `first` and `last`.
+    fn move_span(&self, first: usize, last: usize) -> Option<(usize, usize)> {
+        match *self {
+            StructureChange::Insert { axis, .. } => {
+                let first = self.move_index(first)?;
+                let last = self.move_index(last).unwrap_or(axis.len() - 1);
+                Some((first, last))
+            }
+            StructureChange::Delete { at, .. } => {
+                let first = self.move_index(first).unwrap_or(at);
+                let last = match self.move_index(last) {
+                    Some(last) => last,
+                    None => at.checked_sub(1)?,
+                };
+                Some((first, last)).filter(|(first, last)| first <= last)
+            }
+        }
+    }
+
+    fn at(&self) -> usize {
+        match *self {
+            StructureChange::Insert { at, .. } | StructureChange::Delete { at, .. } => at,
+        }
+    }
+}
@@ -0,0 +1,1148 @@
+//! User interface of the Cells task on top of the [`Sheet`], which holds the state of the
+//! grid. Only the selection, clipboard, editors and column widths live here.
+
+use std::{
+    fmt::{self, Display},
+    path::Path,
+};
+
+use eframe::egui;
+
+use super::{
+    document::{Document, DocumentError},
+    filter::AutoFilter,
+    format::{Align, NumberFormat},
+    formula::{self, ParseError},
+    names::NameTarget,
+    sort::SortKey,
+    structure::{Axis, StructureChange},
+    Area, Coord, CsvExport, Fill, Sheet, COLUMNS, ROWS,
+};
+
+const CELL_WIDTH: f32 = 64.0;
+const MIN_CELL_WIDTH: f32 = 24.0;
+const HEADER_WIDTH: f32 = 32.0;
+
+/// Highlighting of the cells referenced by the edited formula.
+const REFERENCE_FILL: egui::Color32 = egui::Color32::from_rgba_premultiplied(20, 35, 64, 64);
+const REFERENCE_STROKE: egui::Color32 = egui::Color32::from_rgb(80, 140, 255);
+
+pub fn cells(ui: &mut egui::Ui, state: &mut CellsModel) {
+    file_toolbar(ui, state);
+    edit_toolbar(ui, state);
+    format_toolbar(ui, state);
+    names_panel(ui, state);
+    sort_filter_panel(ui, state);
+    keyboard(ui, state);
+    if !ui.input().pointer.any_down() {
+        state.is_selecting = false;
+    }
+
+    formula_bar(ui, state);
+    ui.horizontal(|ui| {
+        ui.label(format!(
+            "Columns from {}",
+            Coord::column_name(state.first_column)
+        ));
+        ui.add(egui::Slider::new(&mut state.first_column, 0..=COLUMNS - 1).show_value(false));
+    });
+
+    let row_height = ui.fonts().row_height(egui::TextStyle::Body) + 4.0;
+
+    let available_width = ui.available_width();
+    if let Some(cursor) = state.selection.map(|selection| selection.cursor) {
+        if state.scroll_to_cursor {
+            state.first_column = state.first_column.min(cursor.column);
+            while HEADER_WIDTH
+                + state.column_widths[state.first_column..=cursor.column]
+                    .iter()
+                    .sum::<f32>()
+                > available_width
+                && state.first_column < cursor.column
+            {
+                state.first_column += 1;
+            }
+        }
+    }
+
+    // Only the columns fitting into the available width are shown, starting with the first
+    // column chosen above, because the scroll area can only scroll vertically.
+    let mut columns = Vec::new();
+    let mut x = HEADER_WIDTH;
+    for column in state.first_column..COLUMNS {
+        if x >= available_width {
+            break;
+        }
+        columns.push((column, x));
+        x += state.column_widths[column];
+    }
+    let width = x.min(available_width);
+
+    let (header_rect, _) =
+        ui.allocate_exact_size(egui::vec2(width, row_height), egui::Sense::hover());
+    header_cell(
+        ui,
+        egui::Rect::from_min_size(header_rect.min, egui::vec2(HEADER_WIDTH, row_height)),
+        "",
+    );
+    for &(column, x) in &columns {
+        let rect = egui::Rect::from_min_size(
+            header_rect.min + egui::vec2(x, 0.0),
+            egui::vec2(state.column_widths[column], row_height),
+        );
+        column_header(ui, rect, column, &mut state.column_widths[column]);
+    }
+
+    let referenced = match state.editing {
+        Some(_) => {
+            if state.referenced.0 != state.editor {
+                let areas = state.sheet.referenced_areas(&state.editor);
+                state.referenced = (state.editor.clone(), areas);
+            }
+            state.referenced.1.clone()
+        }
+        None => Vec::new(),
+    };
+    let hidden_rows = state.sheet.hidden_rows();
+    let rows: Vec<usize> = (0..ROWS).filter(|row| !hidden_rows.contains(row)).collect();
+
+    // Only the rows inside of the viewport are laid out, the rest is just empty space.
+    egui::ScrollArea::from_max_height(row_height * 16.0)
+        .id_source("cells")
+        .show_viewport(ui, |ui, viewport| {
+            let origin = ui.max_rect().min;
+            ui.set_min_width(width);
+            ui.set_min_height(row_height * rows.len() as f32);
+
+            if let Some(cursor) = state.selection.map(|selection| selection.cursor) {
+                let index = rows.binary_search(&cursor.row);
+                if let (true, Ok(index)) = (std::mem::take(&mut state.scroll_to_cursor), index) {
+                    let y = index as f32 * row_height;
+                    let align = if y < viewport.min.y {
+                        Some(egui::Align::Min)
+                    } else if y + row_height > viewport.max.y {
+                        Some(egui::Align::Max)
+                    } else {
+                        None
+                    };
+                    if let Some(align) = align {
+                        let rect = egui::Rect::from_min_size(
+                            origin + egui::vec2(0.0, y),
+                            egui::vec2(width, row_height),
+                        );
+                        ui.interact(rect, ui.id().with("cells_cursor"), egui::Sense::hover())
+                            .scroll_to_me(align);
+                    }
+                }
+            }
+
+            let first_index = (viewport.min.y / row_height).floor().max(0.0) as usize;
+            let last_index = ((viewport.max.y / row_height).ceil() as usize).min(rows.len());
+            for (index, &row) in rows.iter().enumerate().take(last_index).skip(first_index) {
+                let y = index as f32 * row_height;
+                header_cell(
+                    ui,
+                    egui::Rect::from_min_size(
+                        origin + egui::vec2(0.0, y),
+                        egui::vec2(HEADER_WIDTH, row_height),
+                    ),
+                    &row.to_string(),
+                );
+                for &(column, x) in &columns {
+                    let rect = egui::Rect::from_min_size(
+                        origin + egui::vec2(x, y),
+                        egui::vec2(state.column_widths[column], row_height),
+                    );
+                    let coord = Coord { column, row };
+                    let is_referenced = referenced.iter().any(|area| area.contains(coord));
+                    cell(ui, rect, coord, is_referenced, state);
+                }
+            }
+        });
+}
+
+fn file_toolbar(ui: &mut egui::Ui, state: &mut CellsModel) {
+    ui.horizontal(|ui| {
+        ui.label("File");
+        ui.text_edit_singleline(&mut state.file_path);
+
+        if ui.button("Open").clicked() {
+            state.status = match state.open(state.file_path.clone()) {
+                Ok(()) => format!("Opened {}", state.file_path),
+                Err(error) => format!("Opening failed: {}", error),
+            };
+        }
+        if ui.button("Save").clicked() {
+            state.status = match state.save(&state.file_path) {
+                Ok(()) => format!("Saved {}", state.file_path),
+                Err(error) => format!("Saving failed: {}", error),
+            };
+        }
+
+        let anchor = state
+            .selection
+            .map_or(Coord { column: 0, row: 0 }, |selection| {
+                selection.top_left()
+            });
+        if ui.button(format!("Import CSV at {}", anchor)).clicked() {
+            let result = std::fs::read_to_string(&state.file_path)
+                .map_err(|e| e.to_string())
+                .and_then(|input| {
+                    state
+                        .sheet
+                        .import_csv(&input, anchor)
+                        .map_err(|e| e.to_string())
+                });
+            state.status = match result {
+                Ok(()) => format!("Imported {}", state.file_path),
+                Err(error) => format!("Import failed: {}", error),
+            };
+        }
+
+        for (label, export) in &[
+            ("Export formulas", CsvExport::Formulas),
+            ("Export values", CsvExport::Values),
+        ] {
+            if ui.button(label).clicked() {
+                let output = state.sheet.export_csv(*export);
+                state.status = match std::fs::write(&state.file_path, output) {
+                    Ok(()) => format!("Exported {}", state.file_path),
+                    Err(error) => format!("Export failed: {}", error),
+                };
+            }
+        }
+    });
+
+    if !state.status.is_empty() {
+        ui.label(&state.status);
+    }
+}
+
+fn edit_toolbar(ui: &mut egui::Ui, state: &mut CellsModel) {
+    ui.horizontal(|ui| {
+        let undo_button = egui::Button::new("Undo").enabled(state.sheet.can_undo());
+        if ui.add(undo_button).clicked() {
+            state.undo();
+        }
+
+        let redo_button = egui::Button::new("Redo").enabled(state.sheet.can_redo());
+        if ui.add(redo_button).clicked() {
+            state.redo();
+        }
+
+        let clear_button = egui::Button::new("Clear").enabled(state.selection.is_some());
+        if ui.add(clear_button).clicked() {
+            state.clear();
+        }
+
+        for (label, fill) in &[("Fill down", Fill::Down), ("Fill right", Fill::Right)] {
+            let fill_button = egui::Button::new(*label).enabled(state.selection.is_some());
+            if ui.add(fill_button).clicked() {
+                state.fill(*fill);
+            }
+        }
+    });
+
+    let selection = match state.selection {
+        Some(selection) => selection,
+        None => return,
+    };
+    // Whole rows and columns of the selection.
+    let (top_left, bottom_right) = (selection.top_left(), selection.bottom_right());
+    let rows = (top_left.row, bottom_right.row - top_left.row + 1);
+    let columns = (top_left.column, bottom_right.column - top_left.column + 1);
+    ui.horizontal(|ui| {
+        for &(singular, plural, axis, (at, count)) in &[
+            ("row", "rows", Axis::Rows, rows),
+            ("column", "columns", Axis::Columns, columns),
+        ] {
+            let what = if count == 1 { singular } else { plural };
+            if ui.button(format!("Insert {}", what)).clicked() {
+                state.change_structure(StructureChange::Insert { axis, at, count });
+            }
+            if ui.button(format!("Delete {}", what)).clicked() {
+                state.change_structure(StructureChange::Delete { axis, at, count });
+            }
+        }
+    });
+}
+
+fn format_toolbar(ui: &mut egui::Ui, state: &mut CellsModel) {
+    let selection = match state.selection {
+        Some(selection) => selection,
+        None => return,
+    };
+    let mut format = state.sheet.format(selection.cursor).clone();
+    ui.horizontal(|ui| {
+        ui.label(format!("Align {}", selection));
+        for align in &[Align::Auto, Align::Left, Align::Center, Align::Right] {
+            ui.selectable_value(&mut format.align, *align, align.as_str());
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Number");
+        let decimals = format.number.decimals().unwrap_or(2);
+        egui::ComboBox::from_id_source("cells_number_format")
+            .selected_text(format.number.as_str())
+            .show_ui(ui, |ui| {
+                for number in &NumberFormat::all(decimals) {
+                    ui.selectable_value(&mut format.number, *number, number.as_str());
+                }
+            });
+        if let Some(decimals) = format.number.decimals_mut() {
+            ui.add(
+                egui::DragValue::new(decimals)
+                    .clamp_range(0..=10)
+                    .suffix(" decimals"),
+            );
+        }
+    });
+    if format != *state.sheet.format(selection.cursor) {
+        let formats = selection.coords().map(|coord| (coord, format.clone()));
+        state.sheet.set_formats(formats);
+    }
+}
+
+/// Address and input of the cell under the cursor, which can be edited here as well as in the
+/// cell itself, with suggestions for the function or name at the end of a formula.
+fn formula_bar(ui: &mut egui::Ui, state: &mut CellsModel) {
+    let coord = match state
+        .editing
+        .or_else(|| state.selection.map(|selection| selection.cursor))
+    {
+        Some(coord) => coord,
+        None => {
+            state.formula_bar = egui::Rect::NOTHING;
+            return;
+        }
+    };
+    let id = egui::Id::new("cells_formula_bar");
+    let was_over_formula_bar = is_pointer_over(ui, state.formula_bar);
+    let response = ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            ui.add(egui::Label::new(coord.to_string()).monospace());
+            let mut text = if state.editing == Some(coord) {
+                state.editor.clone()
+            } else {
+                state.sheet.input(coord).to_string()
+            };
+            let text_edit = egui::TextEdit::singleline(&mut text)
+                .id(id)
+                .desired_width(f32::INFINITY);
+            let response = ui.add(text_edit);
+            if response.changed() {
+                state.editing = Some(coord);
+                state.editor = text;
+            }
+            if response.lost_focus() && state.editing == Some(coord) {
+                let input = ui.input();
+                if input.key_pressed(egui::Key::Escape) {
+                    state.cancel_editing();
+                } else if input.key_pressed(egui::Key::Enter) || !was_over_formula_bar {
+                    state.commit_editing();
+                }
+            }
+        });
+
+        let (prefix_len, completions) = match state.editing.and(completion_prefix(&state.editor)) {
+            Some(prefix) => (prefix.len(), state.sheet.completions(prefix)),
+            None => (0, Vec::new()),
+        };
+        if !completions.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                for completion in completions {
+                    if ui.small_button(&completion).clicked() {
+                        let len = state.editor.len() - prefix_len;
+                        state.editor.truncate(len);
+                        state.editor.push_str(&completion);
+                        ui.memory().request_focus(id);
+                    }
+                }
+            });
+        }
+    });
+    state.formula_bar = response.response.rect;
+}
+
+/// Identifier at the end of a formula that might be the beginning of a function or a name.
+fn completion_prefix(input: &str) -> Option<&str> {
+    // Inside of a string nothing is completed.
+    if !input.starts_with('=') || input.matches('"').count() % 2 == 1 {
+        return None;
+    }
+    let start = input
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| !c.is_ascii_alphanumeric() && c != '_')
+        .map_or(0, |(index, c)| index + c.len_utf8());
+    let prefix = &input[start..];
+    let is_absolute = input[..start].ends_with('$');
+    let starts_with_letter = prefix.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+    Some(prefix).filter(|_| starts_with_letter && !is_absolute)
+}
+
+fn is_pointer_over(ui: &egui::Ui, rect: egui::Rect) -> bool {
+    ui.input()
+        .pointer
+        .interact_pos()
+        .is_some_and(|pos| rect.contains(pos))
+}
+
+fn names_panel(ui: &mut egui::Ui, state: &mut CellsModel) {
+    egui::CollapsingHeader::new("Names")
+        .id_source("cells_names")
+        .show(ui, |ui| {
+            let names: Vec<(String, NameTarget)> = state
+                .sheet
+                .names()
+                .map(|(name, target)| (name.to_string(), target))
+                .collect();
+            egui::Grid::new("cells_names_grid").show(ui, |ui| {
+                for (name, target) in names {
+                    ui.label(&name);
+                    ui.label(target.to_string());
+                    if ui.small_button("Edit").clicked() {
+                        state.target_editor = target.to_string();
+                        state.name_editor = name.clone();
+                        state.renaming = Some(name.clone());
+                    }
+                    if ui.small_button("Delete").clicked() {
+                        state.sheet.remove_name(&name);
+                    }
+                    ui.end_row();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Name");
+                ui.add(egui::TextEdit::singleline(&mut state.name_editor).desired_width(100.0));
+                ui.label("refers to");
+                ui.add(egui::TextEdit::singleline(&mut state.target_editor).desired_width(100.0));
+                if let Some(selection) = state.selection {
+                    if ui.button("Use selection").clicked() {
+                        state.target_editor = NameTarget::from(selection).to_string();
+                    }
+                }
+
+                let label = if state.renaming.is_some() {
+                    "Update"
+                } else {
+                    "Define"
+                };
+                if ui.button(label).clicked() {
+                    state.status = match submit_name(state) {
+                        Ok(()) => String::new(),
+                        Err(error) => error,
+                    };
+                }
+                if state.renaming.is_some() && ui.button("Cancel").clicked() {
+                    state.renaming = None;
+                    state.name_editor.clear();
+                    state.target_editor.clear();
+                }
+            });
+        });
+}
+
+/// Defines the name in the editor, or renames the edited name and updates its target.
+fn submit_name(state: &mut CellsModel) -> Result<(), String> {
+    let target = state
+        .target_editor
+        .parse::<NameTarget>()
+        .map_err(|()| format!("`{}` is not a cell or range", state.target_editor.trim()))?;
+    let name = state.name_editor.trim().to_string();
+    match state.renaming.clone() {
+        Some(old) if !old.eq_ignore_ascii_case(&name) => state.sheet.rename_name(&old, &name),
+        _ => Ok(()),
+    }
+    .and_then(|()| state.sheet.define_name(&name, target))
+    .map_err(|error| error.to_string())?;
+
+    state.renaming = None;
+    state.name_editor.clear();
+    state.target_editor.clear();
+    Ok(())
+}
+
+fn sort_filter_panel(ui: &mut egui::Ui, state: &mut CellsModel) {
+    let selection = match state.selection {
+        Some(selection) => selection,
+        None => return,
+    };
+    egui::CollapsingHeader::new("Sort and filter")
+        .id_source("cells_sort_filter")
+        .show(ui, |ui| {
+            let (top_left, bottom_right) = (selection.top_left(), selection.bottom_right());
+            let columns = top_left.column..=bottom_right.column;
+            if state.sort_keys.is_empty() {
+                state.sort_keys.push(SortKey {
+                    column: top_left.column,
+                    descending: false,
+                });
+            }
+
+            let mut removed = None;
+            for (i, key) in state.sort_keys.iter_mut().enumerate() {
+                if !columns.contains(&key.column) {
+                    key.column = top_left.column;
+                }
+                ui.horizontal(|ui| {
+                    ui.label(if i == 0 { "Sort by" } else { "then by" });
+                    egui::ComboBox::from_id_source(("cells_sort_column", i))
+                        .selected_text(Coord::column_name(key.column))
+                        .show_ui(ui, |ui| {
+                            for column in columns.clone() {
+                                let name = Coord::column_name(column);
+                                ui.selectable_value(&mut key.column, column, name);
+                            }
+                        });
+                    ui.selectable_value(&mut key.descending, false, "Ascending");
+                    ui.selectable_value(&mut key.descending, true, "Descending");
+                    if i > 0 && ui.small_button("Remove").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = removed {
+                state.sort_keys.remove(i);
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Add column").clicked() {
+                    state.sort_keys.push(SortKey {
+                        column: top_left.column,
+                        descending: false,
+                    });
+                }
+                ui.checkbox(&mut state.sort_header, "First row is a header");
+                if ui.button(format!("Sort {}", selection)).clicked() {
+                    let keys = state.sort_keys.clone();
+                    state.sort(&keys, state.sort_header);
+                }
+            });
+
+            ui.separator();
+            match state.sheet.filter().cloned() {
+                Some(mut filter) => {
+                    ui.label(format!(
+                        "Filtering {}:{}: enter text to search for or a comparison like `>10`, \
+                         `<>done` or `=` for empty cells",
+                        filter.start(),
+                        filter.end()
+                    ));
+                    let header_row = filter.start().row;
+                    let columns: Vec<usize> = filter.columns().collect();
+                    egui::Grid::new("cells_filter_grid").show(ui, |ui| {
+                        for column in columns {
+                            let header = state.sheet.value(Coord {
+                                column,
+                                row: header_row,
+                            });
+                            ui.label(format!("{} {}", Coord::column_name(column), header));
+                            let mut criterion = filter.criterion(column).to_string();
+                            if ui.text_edit_singleline(&mut criterion).changed() {
+                                filter.set_criterion(column, &criterion);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                    if ui.button("Remove filter").clicked() {
+                        state.sheet.set_filter(None);
+                    } else if Some(&filter) != state.sheet.filter() {
+                        state.sheet.set_filter(Some(filter));
+                    }
+                }
+                None => {
+                    if ui.button(format!("Filter {}", selection)).clicked() {
+                        let filter = AutoFilter::new(top_left, bottom_right);
+                        state.sheet.set_filter(Some(filter));
+                    }
+                }
+            }
+        });
+}
+
+/// Moves the selection with the arrow keys, extending it while shift is held, and handles
+/// clipboard and history shortcuts while no cell is edited.
+fn keyboard(ui: &egui::Ui, state: &mut CellsModel) {
+    if state.editing.is_some() || ui.memory().focus().is_some() {
+        return;
+    }
+    let input = ui.input().clone();
+    let (mut columns, mut rows) = (0, 0);
+    for (key, column, row) in &[
+        (egui::Key::ArrowLeft, -1, 0),
+        (egui::Key::ArrowRight, 1, 0),
+        (egui::Key::ArrowUp, 0, -1),
+        (egui::Key::ArrowDown, 0, 1),
+    ] {
+        let presses = input.num_presses(*key) as isize;
+        columns += column * presses;
+        rows += row * presses;
+    }
+    if let Some(selection) = state.selection.filter(|_| (columns, rows) != (0, 0)) {
+        if let Some(cursor) = selection.cursor.offset(columns, rows) {
+            state.selection = Some(if input.modifiers.shift {
+                Selection {
+                    cursor,
+                    ..selection
+                }
+            } else {
+                Selection::new(cursor)
+            });
+            state.scroll_to_cursor = true;
+        }
+    }
+
+    // Pasting arrives as text together with the key that triggered it.
+    let mut is_paste = false;
+    let mut pasted_text = None;
+    for event in &input.events {
+        match event {
+            egui::Event::Copy => {
+                if let Some(text) = state.copy() {
+                    ui.output().copied_text = text;
+                }
+            }
+            egui::Event::Key {
+                key: egui::Key::V,
+                pressed: true,
+                modifiers,
+            } if modifiers.command => is_paste = true,
+            egui::Event::Text(text) => pasted_text = Some(text),
+            _ => {}
+        }
+    }
+    if is_paste {
+        match pasted_text {
+            Some(text) if !state.is_copied_text(text) => state.paste_text(text),
+            _ => state.paste(),
+        }
+    }
+
+    if input.key_pressed(egui::Key::Delete) || input.key_pressed(egui::Key::Backspace) {
+        state.clear();
+    }
+    if input.modifiers.command && input.key_pressed(egui::Key::Z) {
+        if input.modifiers.shift {
+            state.redo();
+        } else {
+            state.undo();
+        }
+    } else if input.modifiers.command && input.key_pressed(egui::Key::Y) {
+        state.redo();
+    }
+    if input.modifiers.command && input.key_pressed(egui::Key::D) {
+        state.fill(Fill::Down);
+    } else if input.modifiers.command && input.key_pressed(egui::Key::R) {
+        state.fill(Fill::Right);
+    }
+}
+
+/// Header of a column, which can be resized by dragging its right border.
+fn column_header(ui: &mut egui::Ui, rect: egui::Rect, column: usize, width: &mut f32) {
+    header_cell(ui, rect, &Coord::column_name(column));
+    let handle =
+        egui::Rect::from_x_y_ranges(rect.right() - 3.0..=rect.right() + 3.0, rect.y_range());
+    let response = ui
+        .interact(
+            handle,
+            ui.id().with(("column_resize", column)),
+            egui::Sense::drag(),
+        )
+        .on_hover_cursor(egui::CursorIcon::ResizeHorizontal);
+    if response.dragged() {
+        *width = (*width + response.drag_delta().x).max(MIN_CELL_WIDTH);
+    }
+}
+
+fn header_cell(ui: &mut egui::Ui, rect: egui::Rect, text: &str) {
+    let visuals = ui.style().visuals.widgets.noninteractive;
+    ui.painter().rect_filled(rect, 0.0, visuals.bg_fill);
+    ui.painter().rect_stroke(rect, 0.0, visuals.bg_stroke);
+    ui.painter().text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        text,
+        egui::TextStyle::Body,
+        visuals.text_color(),
+    );
+}
+
+fn cell(
+    ui: &mut egui::Ui,
+    rect: egui::Rect,
+    coord: Coord,
+    is_referenced: bool,
+    state: &mut CellsModel,
+) {
+    if state.editing == Some(coord) {
+        let text_edit = egui::TextEdit::singleline(&mut state.editor)
+            .id_source(("cells_editor", coord))
+            .desired_width(rect.width());
+        let response = ui.put(rect, text_edit);
+        if state.is_editor_opened {
+            response.request_focus();
+            state.is_editor_opened = false;
+        } else if response.lost_focus() {
+            if ui.input().key_pressed(egui::Key::Escape) {
+                state.cancel_editing();
+            } else if !is_pointer_over(ui, state.formula_bar) {
+                // Clicking into the formula bar continues editing there.
+                state.commit_editing();
+            }
+            return;
+        }
+
+        if state.editor.starts_with('=') {
+            let formula = formula::parse(&state.editor);
+            if !formula.is_valid() {
+                let id = ui.make_persistent_id(("cells_editor_errors", coord));
+                egui::show_tooltip_under(ui.ctx(), id, &response.rect, |ui| {
+                    parse_errors(ui, &state.editor, &formula.errors)
+                });
+            }
+        }
+        return;
+    }
+
+    let response = ui.allocate_rect(rect, egui::Sense::click_and_drag());
+    let visuals = ui.style().interact(&response);
+    let selection = state
+        .selection
+        .filter(|selection| selection.contains(coord));
+    if selection.is_some() {
+        ui.painter()
+            .rect_filled(rect, 0.0, ui.style().visuals.selection.bg_fill);
+    }
+    if is_referenced {
+        ui.painter().rect_filled(rect, 0.0, REFERENCE_FILL);
+    }
+    let (value, format) = (state.sheet.value(coord), state.sheet.format(coord));
+    let errors = state.sheet.errors(coord);
+    let is_in_cycle = state.sheet.is_in_cycle(coord);
+
+    let stroke = if is_referenced {
+        egui::Stroke::new(1.0, REFERENCE_STROKE)
+    } else if errors.is_empty() && !is_in_cycle {
+        ui.style().visuals.widgets.noninteractive.bg_stroke
+    } else {
+        egui::Stroke::new(1.0, egui::Color32::RED)
+    };
+    ui.painter().rect_stroke(rect, 0.0, stroke);
+    if selection.is_some_and(|selection| selection.cursor == coord) {
+        ui.painter()
+            .rect_stroke(rect, 0.0, ui.style().visuals.selection.stroke);
+    }
+
+    let text_rect = rect.shrink2(egui::vec2(4.0, 0.0));
+    let (align, text_pos) = match format.resolve_align(value) {
+        Align::Right => (egui::Align2::RIGHT_CENTER, text_rect.right_center()),
+        Align::Center => (egui::Align2::CENTER_CENTER, text_rect.center()),
+        _ => (egui::Align2::LEFT_CENTER, text_rect.left_center()),
+    };
+    let text_color = if value.is_error() {
+        egui::Color32::RED
+    } else {
+        visuals.text_color()
+    };
+    ui.painter_at(rect).text(
+        text_pos,
+        align,
+        format.display(value),
+        egui::TextStyle::Body,
+        text_color,
+    );
+
+    let response = if !errors.is_empty() {
+        response.on_hover_ui(|ui| parse_errors(ui, state.sheet.input(coord), errors))
+    } else if is_in_cycle {
+        // Cycles can be long, so the path is only built while it is shown.
+        response.on_hover_ui(|ui| {
+            let cycle = state.sheet.cycle(coord).unwrap_or_default();
+            let path: Vec<String> = cycle.iter().map(Coord::to_string).collect();
+            ui.label(format!("Circular reference: {}", path.join(" → ")));
+        })
+    } else {
+        response
+    };
+    let shift = ui.input().modifiers.shift;
+    let extend = |selection: Option<Selection>| match selection {
+        Some(selection) if shift => Selection {
+            cursor: coord,
+            ..selection
+        },
+        _ => Selection::new(coord),
+    };
+    if response.drag_started() {
+        state.selection = Some(extend(state.selection));
+        state.is_selecting = true;
+    } else if state.is_selecting && ui.rect_contains_pointer(rect) {
+        if let Some(selection) = &mut state.selection {
+            selection.cursor = coord;
+        }
+    }
+    if response.clicked() {
+        state.selection = Some(extend(state.selection));
+    }
+    if response.double_clicked() {
+        state.start_editing(coord);
+    }
+}
+
+/// Lists every error with the input, in which the invalid part is highlighted.
+fn parse_errors(ui: &mut egui::Ui, input: &str, errors: &[ParseError]) {
+    for error in errors {
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            let (before, invalid, after) = (
+                &input[..error.span.start],
+                &input[error.span.clone()],
+                &input[error.span.end..],
+            );
+            ui.add(egui::Label::new(before).monospace());
+            // Mark the position with a space if the input ended too early.
+            let invalid = if invalid.is_empty() { " " } else { invalid };
+            ui.add(
+                egui::Label::new(invalid)
+                    .monospace()
+                    .background_color(egui::Color32::RED)
+                    .text_color(egui::Color32::WHITE),
+            );
+            ui.add(egui::Label::new(after).monospace());
+        });
+        ui.label(&error.message);
+    }
+}
+
+/// State of the Cells task: a [`Sheet`] together with how it is shown and edited.
+pub struct CellsModel {
+    sheet: Sheet,
+    column_widths: Vec<f32>,
+    first_column: usize,
+    selection: Option<Selection>,
+    /// The selection follows the pointer while the mouse button is held.
+    is_selecting: bool,
+    scroll_to_cursor: bool,
+    clipboard: Option<Clipboard>,
+    editing: Option<Coord>,
+    editor: String,
+    /// Areas read by the formula in the editor and the text they were found in, which is only
+    /// parsed again when the text changes.
+    referenced: (String, Vec<Area>),
+    is_editor_opened: bool,
+    name_editor: String,
+    target_editor: String,
+    /// Name whose target is shown in the editors, which is renamed to the edited name.
+    renaming: Option<String>,
+    /// Area of the formula bar in the last frame, where clicking does not end editing.
+    formula_bar: egui::Rect,
+    sort_keys: Vec<SortKey>,
+    /// The first row of the selection stays in place when sorting.
+    sort_header: bool,
+    file_path: String,
+    status: String,
+}
+
+impl Default for CellsModel {
+    fn default() -> Self {
+        Self {
+            sheet: Sheet::new(),
+            column_widths: vec![CELL_WIDTH; COLUMNS],
+            first_column: 0,
+            selection: None,
+            is_selecting: false,
+            scroll_to_cursor: false,
+            clipboard: None,
+            editing: None,
+            editor: String::new(),
+            referenced: (String::new(), Vec::new()),
+            is_editor_opened: false,
+            name_editor: String::new(),
+            target_editor: String::new(),
+            renaming: None,
+            formula_bar: egui::Rect::NOTHING,
+            sort_keys: Vec::new(),
+            sort_header: true,
+            file_path: String::from("cells.json"),
+            status: String::new(),
+        }
+    }
+}
+
+impl CellsModel {
+    pub fn sheet(&self) -> &Sheet {
+        &self.sheet
+    }
+
+    pub fn sheet_mut(&mut self) -> &mut Sheet {
+        &mut self.sheet
+    }
+
+    fn start_editing(&mut self, coord: Coord) {
+        if let Some(previous) = self.editing {
+            if previous != coord {
+                self.commit_editing();
+            }
+        }
+        self.editor = self.sheet.input(coord).to_string();
+        self.editing = Some(coord);
+        self.is_editor_opened = true;
+    }
+
+    fn cancel_editing(&mut self) {
+        self.editing = None;
+        self.editor.clear();
+    }
+
+    fn commit_editing(&mut self) {
+        if let Some(coord) = self.editing.take() {
+            let text = std::mem::take(&mut self.editor);
+            self.sheet.set(coord, text);
+        }
+    }
+
+    /// Reverts the last change and selects the cells it affected.
+    pub fn undo(&mut self) {
+        self.cancel_editing();
+        if let Some((anchor, cursor)) = self.sheet.undo() {
+            self.select(Selection { anchor, cursor });
+        }
+    }
+
+    /// Applies the last undone change again and selects the cells it affected.
+    pub fn redo(&mut self) {
+        self.cancel_editing();
+        if let Some((anchor, cursor)) = self.sheet.redo() {
+            self.select(Selection { anchor, cursor });
+        }
+    }
+
+    /// Removes the input of all selected cells.
+    pub fn clear(&mut self) {
+        if let Some(selection) = self.selection {
+            self.sheet.clear(selection.anchor, selection.cursor);
+        }
+    }
+
+    /// Fills the selection, see [`Sheet::fill`].
+    pub fn fill(&mut self, fill: Fill) {
+        if let Some(selection) = self.selection {
+            self.sheet.fill(selection.anchor, selection.cursor, fill);
+        }
+    }
+
+    /// Sorts the rows of the selection, see [`Sheet::sort`].
+    pub fn sort(&mut self, keys: &[SortKey], has_header: bool) {
+        self.cancel_editing();
+        if let Some(selection) = self.selection {
+            self.sheet
+                .sort(selection.anchor, selection.cursor, keys, has_header);
+        }
+    }
+
+    pub fn selection(&self) -> Option<Selection> {
+        self.selection
+    }
+
+    pub fn select(&mut self, selection: Selection) {
+        self.selection = Some(selection);
+        self.scroll_to_cursor = true;
+    }
+
+    /// Remembers the inputs of the selected cells for pasting and returns them as tab separated
+    /// text for other applications.
+    pub fn copy(&mut self) -> Option<String> {
+        let selection = self.selection?;
+        let inputs = self.sheet.inputs(selection.anchor, selection.cursor);
+        let text = inputs
+            .iter()
+            .map(|row| row.join("\t"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.clipboard = Some(Clipboard {
+            origin: selection.top_left(),
+            inputs,
+            text: text.clone(),
+        });
+        Some(text)
+    }
+
+    fn is_copied_text(&self, text: &str) -> bool {
+        self.clipboard
+            .as_ref()
+            .is_some_and(|clipboard| clipboard.text == text)
+    }
+
+    /// Pastes the copied cells with their top left corner at the selection. References in
+    /// formulas move along with the cells, unless they are absolute.
+    pub fn paste(&mut self) {
+        let (clipboard, selection) = match (&self.clipboard, self.selection) {
+            (Some(clipboard), Some(selection)) => (clipboard, selection),
+            _ => return,
+        };
+        let pasted = self
+            .sheet
+            .paste(&clipboard.inputs, clipboard.origin, selection.top_left());
+        self.select_pasted(pasted);
+    }
+
+    /// Pastes tab separated text, e.g. from another application, at the selection as it is.
+    pub fn paste_text(&mut self, text: &str) {
+        let target = match self.selection {
+            Some(selection) => selection.top_left(),
+            None => return,
+        };
+        let rows: Vec<Vec<String>> = text
+            .lines()
+            .map(|line| line.split('\t').map(str::to_string).collect())
+            .collect();
+        let pasted = self.sheet.paste(&rows, target, target);
+        self.select_pasted(pasted);
+    }
+
+    fn select_pasted(&mut self, pasted: Option<(Coord, Coord)>) {
+        if let Some((anchor, cursor)) = pasted {
+            self.selection = Some(Selection { anchor, cursor });
+        }
+    }
+
+    /// Writes the grid with formulas, formats and column widths as a [`Document`].
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), DocumentError> {
+        std::fs::write(path, self.to_document().to_json())?;
+        Ok(())
+    }
+
+    /// Replaces the whole grid with the content of a document file. The grid is left untouched
+    /// if the file cannot be read.
+    pub fn open(&mut self, path: impl AsRef<Path>) -> Result<(), DocumentError> {
+        let document = Document::from_json(&std::fs::read_to_string(path)?)?;
+        self.load_document(document);
+        Ok(())
+    }
+
+    pub fn to_document(&self) -> Document {
+        let mut document = self.sheet.to_document();
+        for (column, &width) in self.column_widths.iter().enumerate() {
+            if width != CELL_WIDTH {
+                document
+                    .column_widths
+                    .insert(Coord::column_name(column), width);
+            }
+        }
+        document
+    }
+
+    /// Replaces the whole grid with the document, which starts a new history. Widths of unknown
+    /// columns and invalid names are ignored.
+    pub fn load_document(&mut self, document: Document) {
+        self.cancel_editing();
+        self.column_widths = vec![CELL_WIDTH; COLUMNS];
+        for (name, width) in &document.column_widths {
+            if let Some(column) = Coord::parse_column(name) {
+                self.column_widths[column] = width.max(MIN_CELL_WIDTH);
+            }
+        }
+        self.sheet.load_document(document);
+    }
+
+    /// Inserts or deletes rows or columns, see [`Sheet::change_structure`], together with the
+    /// widths of the columns.
+    pub fn change_structure(&mut self, change: StructureChange) {
+        self.cancel_editing();
+        self.sheet.change_structure(change);
+
+        if change.axis() == Axis::Columns {
+            match change {
+                StructureChange::Insert { at, count, .. } => {
+                    let count = count.min(COLUMNS - at);
+                    self.column_widths.splice(at..at, vec![CELL_WIDTH; count]);
+                    self.column_widths.truncate(COLUMNS);
+                }
+                StructureChange::Delete { at, count, .. } => {
+                    self.column_widths.drain(at..(at + count).min(COLUMNS));
+                    self.column_widths.resize(COLUMNS, CELL_WIDTH);
+                }
+            }
+        }
+        if let Some(selection) = self.selection {
+            let fallback = |coord: Coord| Coord {
+                column: coord.column.min(COLUMNS - 1),
+                row: coord.row.min(ROWS - 1),
+            };
+            self.selection = Some(Selection {
+                anchor: fallback(selection.anchor),
+                cursor: fallback(selection.cursor),
+            });
+        }
+    }
+}
+
+/// Copied cells together with the place they were copied from.
+struct Clipboard {
+    origin: Coord,
+    inputs: Vec<Vec<String>>,
+    /// Text handed to the system clipboard, to recognize it when it is pasted again.
+    text: String,
+}
+
+/// Rectangular area of cells between the cell where selecting started and the cursor, both
+/// included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    pub anchor: Coord,
+    pub cursor: Coord,
+}
+
+impl Selection {
+    pub fn new(coord: Coord) -> Self {
+        Self {
+            anchor: coord,
+            cursor: coord,
+        }
+    }
+
+    pub fn top_left(&self) -> Coord {
+        Coord {
+            column: self.anchor.column.min(self.cursor.column),
+            row: self.anchor.row.min(self.cursor.row),
+        }
+    }
+
+    pub fn bottom_right(&self) -> Coord {
+        Coord {
+            column: self.anchor.column.max(self.cursor.column),
+            row: self.anchor.row.max(self.cursor.row),
+        }
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        let (top_left, bottom_right) = (self.top_left(), self.bottom_right());
+        (top_left.column..=bottom_right.column).contains(&coord.column)
+            && (top_left.row..=bottom_right.row).contains(&coord.row)
+    }
+
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        Coord::range(self.anchor, self.cursor)
+    }
+}
+
+impl From<Selection> for NameTarget {
+    fn from(selection: Selection) -> Self {
+        if selection.anchor == selection.cursor {
+            NameTarget::Cell(selection.anchor)
+        } else {
+            NameTarget::Range(selection.top_left(), selection.bottom_right())
+        }
+    }
+}
+
+impl Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.anchor == self.cursor {
+            write!(f, "{}", self.anchor)
+        } else {
+            write!(f, "{}:{}", self.top_left(), self.bottom_right())
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    fmt::{self, Display},
+};
+
+/// Computed value of a cell.
+///
+/// Values are converted on demand, when an operation expects a different type:
+///
+/// | from      | number               | boolean                    | text              |
+/// |-----------|----------------------|----------------------------|-------------------|
+/// | empty     | `0`                  | `false`                    | `""`              |
+/// | number    | itself               | `false` if `0`             | formatted number  |
+/// | text      | parsed or `#VALUE`   | `true`/`false` or `#VALUE` | itself            |
+/// | boolean   | `1` or `0`           | itself                     | `TRUE` or `FALSE` |
+///
+/// Errors cannot be converted and are passed on instead, so that a cell depending on an
+/// erroneous cell shows the original error.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum CellValue {
+    #[default]
+    Empty,
+    Number(f64),
+    Text(String),
+    Boolean(bool),
+    Error(ErrorKind),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// The formula could not be parsed.
+    Parse,
+    /// A reference points to a cell that does not exist.
+    Ref,
+    /// Division by zero.
+    Div0,
+    /// Unknown function or name.
+    Name,
+    /// A value has the wrong type, e.g. text that is not a number in a calculation.
+    Value,
+    /// The result is not a finite number.
+    Num,
+    /// The cell is part of a circular reference.
+    Cycle,
+}
+
+impl ErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::Parse => "#PARSE",
+            ErrorKind::Ref => "#REF",
+            ErrorKind::Div0 => "#DIV/0",
+            ErrorKind::Name => "#NAME",
+            ErrorKind::Value => "#VALUE",
+            ErrorKind::Num => "#NUM",
+            ErrorKind::Cycle => "#CYCLE",
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parses a number as it is typed into a cell. Only finite numbers are numbers, so that `inf`
+/// and `NaN` are text like any other word.
+pub fn parse_number(text: &str) -> Option<f64> {
+    text.parse().ok().filter(|number: &f64| number.is_finite())
+}
+
+impl CellValue {
+    /// Wraps the result of a calculation, which is an error if it is not finite.
+    pub fn number(number: f64) -> Self {
+        if number.is_finite() {
+            CellValue::Number(number)
+        } else {
+            CellValue::Error(ErrorKind::Num)
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, CellValue::Error(_))
+    }
+
+    pub fn as_number(&self) -> Result<f64, ErrorKind> {
+        match self {
+            CellValue::Empty => Ok(0.0),
+            CellValue::Number(number) => Ok(*number),
+            CellValue::Text(text) => parse_number(text.trim()).ok_or(ErrorKind::Value),
+            CellValue::Boolean(boolean) => Ok(f64::from(u8::from(*boolean))),
+            CellValue::Error(error) => Err(*error),
+        }
+    }
+
+    pub fn as_boolean(&self) -> Result<bool, ErrorKind> {
+        match self {
+            CellValue::Empty => Ok(false),
+            CellValue::Number(number) => Ok(*number != 0.0),
+            CellValue::Text(text) if text.trim().eq_ignore_ascii_case("true") => Ok(true),
+            CellValue::Text(text) if text.trim().eq_ignore_ascii_case("false") => Ok(false),
+            CellValue::Text(_) => Err(ErrorKind::Value),
+            CellValue::Boolean(boolean) => Ok(*boolean),
+            CellValue::Error(error) => Err(*error),
+        }
+    }
+
+    pub fn as_text(&self) -> Result<Cow<'_, str>, ErrorKind> {
+        match self {
+            CellValue::Text(text) => Ok(Cow::Borrowed(text)),
+            CellValue::Error(error) => Err(*error),
+            _ => Ok(Cow::Owned(self.to_string())),
+        }
+    }
+
+    /// Orders values like spreadsheets do: numbers before text before booleans, where text is
+    /// compared case insensitively. Empty cells are equal to `0`, `""` and `false`.
+    pub fn compare(&self, other: &CellValue) -> Result<Ordering, ErrorKind> {
+        fn rank(value: &CellValue) -> u8 {
+            match value {
+                CellValue::Empty | CellValue::Number(_) => 0,
+                CellValue::Text(_) => 1,
+                CellValue::Boolean(_) => 2,
+                CellValue::Error(_) => 3,
+            }
+        }
+
+        match (self, other) {
+            (CellValue::Error(error), _) | (_, CellValue::Error(error)) => Err(*error),
+            (CellValue::Empty, CellValue::Text(text)) => Ok("".cmp(text.as_str())),
+            (CellValue::Text(text), CellValue::Empty) => Ok(text.as_str().cmp("")),
+            (CellValue::Empty, CellValue::Boolean(boolean)) => Ok(false.cmp(boolean)),
+            (CellValue::Boolean(boolean), CellValue::Empty) => Ok(boolean.cmp(&false)),
+            (CellValue::Text(lhs), CellValue::Text(rhs)) => {
+                Ok(lhs.to_lowercase().cmp(&rhs.to_lowercase()))
+            }
+            (CellValue::Boolean(lhs), CellValue::Boolean(rhs)) => Ok(lhs.cmp(rhs)),
+            (lhs, rhs) if rank(lhs) == 0 && rank(rhs) == 0 => {
+                let (lhs, rhs) = (lhs.as_number()?, rhs.as_number()?);
+                Ok(lhs.partial_cmp(&rhs).unwrap_or(Ordering::Equal))
+            }
+            (lhs, rhs) => Ok(rank(lhs).cmp(&rank(rhs))),
+        }
+    }
+}
+
+impl From<ErrorKind> for CellValue {
+    fn from(error: ErrorKind) -> Self {
+        CellValue::Error(error)
+    }
+}
+
+impl Display for CellValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellValue::Empty => Ok(()),
+            CellValue::Number(number) => write!(f, "{}", number),
+            CellValue::Text(text) => f.write_str(text),
+            CellValue::Boolean(true) => f.write_str("TRUE"),
+            CellValue::Boolean(false) => f.write_str("FALSE"),
+            CellValue::Error(error) => error.fmt(f),
+        }
+    }
+}
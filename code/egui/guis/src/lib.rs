@@ -1,8 +1,21 @@
+pub mod cells;
+#[cfg(feature = "gui")]
 pub mod circle_drawer;
+#[cfg(feature = "gui")]
 pub mod counter;
+#[cfg(feature = "gui")]
 pub mod crud;
+#[cfg(feature = "gui")]
 pub mod flight_booker;
+#[cfg(feature = "gui")]
 pub mod temperature_converter;
+#[cfg(feature = "gui")]
 pub mod timer;
+#[cfg(feature = "gui")]
+pub mod unit_converter;
 
+#[cfg(feature = "gui")]
 mod util;
+
+#[cfg(feature = "gui")]
+pub use util::{NumberEdit, NumberText};
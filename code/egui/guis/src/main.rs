@@ -1,7 +1,8 @@
 use eframe::{egui, epi};
 
 use guis::{
-    circle_drawer::*, counter::*, crud::*, flight_booker::*, temperature_converter::*, timer::*,
+    cells::*, circle_drawer::*, counter::*, crud::*, flight_booker::*, temperature_converter::*,
+    timer::*,
 };
 
 #[derive(Default)]
@@ -12,6 +13,7 @@ struct App {
     timer_model: TimerModel,
     crud_model: CrudModel,
     circle_drawer_model: CircleDrawerModel,
+    cells_model: CellsModel,
 }
 
 impl epi::App for App {
@@ -35,9 +37,7 @@ impl epi::App for App {
                 task_container(ui, "Circle Drawer", |ui| {
                     circle_drawer(ui, &mut self.circle_drawer_model, viewport)
                 });
-                task_container(ui, "Cells", |ui| {
-                    ui.label("TODO");
-                });
+                task_container(ui, "Cells", |ui| cells(ui, &mut self.cells_model));
             })
         });
     }
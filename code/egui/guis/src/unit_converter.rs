@@ -0,0 +1,218 @@
+//! Converter between the units of a quantity that shows one linked field per unit. Typing into a
+//! field converts its value to every other unit, going through the base unit of the quantity.
+
+use std::fmt::{self, Display};
+
+use eframe::egui::{self, TextBuffer as _};
+
+use crate::util::{NumberEdit, NumberText};
+
+pub fn unit_converter(ui: &mut egui::Ui, state: &mut UnitConverter) {
+    ui.columns(state.units.len(), |columns| {
+        for (index, column) in columns.iter_mut().enumerate() {
+            column.label(state.units[index].name);
+            let is_invalid = state.error.is_some() && index == state.edited;
+            let is_stale = state.error.is_some() && index != state.edited;
+            let text_color = if is_invalid {
+                Some(egui::Color32::RED)
+            } else if is_stale {
+                Some(column.visuals().weak_text_color())
+            } else {
+                None
+            };
+            let response =
+                column.add(NumberEdit::new(&mut state.fields[index]).text_color_opt(text_color));
+            if is_invalid {
+                let corner_radius = column.visuals().widgets.inactive.corner_radius;
+                column.painter().rect_stroke(
+                    response.rect,
+                    corner_radius,
+                    egui::Stroke::new(1.0, egui::Color32::RED),
+                );
+            }
+            let response = if is_stale {
+                response.on_hover_text("Not converted, because the edited field is invalid")
+            } else {
+                response
+            };
+            if response.changed() {
+                state.edited = index;
+                state.update();
+            }
+        }
+    });
+    if let Some(error) = &state.error {
+        ui.colored_label(egui::Color32::RED, error.to_string());
+    }
+
+    let precision = egui::DragValue::new(&mut state.precision)
+        .clamp_range(0..=10)
+        .suffix(" decimals");
+    if ui.add(precision).changed() {
+        state.update();
+    }
+}
+
+/// Unit of a quantity, which converts its values from and to the base unit of the quantity.
+#[derive(Debug, Clone, Copy)]
+pub struct Unit {
+    pub name: &'static str,
+    pub to_base: fn(f64) -> f64,
+    pub from_base: fn(f64) -> f64,
+}
+
+/// Temperature units with Kelvin as base unit.
+pub const TEMPERATURE: &[Unit] = &[
+    Unit {
+        name: "Celsius",
+        to_base: |celsius| celsius + 273.15,
+        from_base: |kelvin| kelvin - 273.15,
+    },
+    Unit {
+        name: "Fahrenheit",
+        to_base: |fahrenheit| (fahrenheit + 459.67) / 1.8,
+        from_base: |kelvin| kelvin * 1.8 - 459.67,
+    },
+    Unit {
+        name: "Kelvin",
+        to_base: |kelvin| kelvin,
+        from_base: |kelvin| kelvin,
+    },
+    Unit {
+        name: "Rankine",
+        to_base: |rankine| rankine / 1.8,
+        from_base: |kelvin| kelvin * 1.8,
+    },
+];
+
+pub struct UnitConverter {
+    units: Vec<Unit>,
+    fields: Vec<NumberText>,
+    /// Most decimals shown in converted fields.
+    precision: u8,
+    /// Index of the field the user typed into last, which the other fields are converted from.
+    edited: usize,
+    /// Why the edited field could not be converted, in which case the other fields still show
+    /// the last conversion.
+    error: Option<ConversionError>,
+    /// Smallest value in the base unit, e.g. absolute zero for temperatures.
+    minimum: f64,
+}
+
+impl UnitConverter {
+    pub fn new(units: &[Unit]) -> Self {
+        Self {
+            units: units.to_vec(),
+            fields: units.iter().map(|_| NumberText::default()).collect(),
+            precision: 1,
+            edited: 0,
+            error: None,
+            minimum: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Rejects values below `minimum` in the base unit, which also keeps fields from accepting a
+    /// minus sign if the minimum of their unit is not negative.
+    pub fn minimum(mut self, minimum: f64) -> Self {
+        self.minimum = minimum;
+        for (unit, field) in self.units.iter().zip(&mut self.fields) {
+            let range = (unit.from_base)(minimum)..=f64::INFINITY;
+            *field = std::mem::take(field).range(range);
+        }
+        self
+    }
+
+    /// Replaces the text of a field as if the user typed it and converts it to the other fields.
+    pub fn edit(&mut self, index: usize, text: &str) {
+        self.fields[index].replace(text);
+        self.edited = index;
+        self.update();
+    }
+
+    /// Texts of the fields in the order of the units.
+    pub fn texts(&self) -> Vec<&str> {
+        self.fields.iter().map(|field| field.as_str()).collect()
+    }
+
+    pub fn error(&self) -> Option<&ConversionError> {
+        self.error.as_ref()
+    }
+
+    /// Converts the edited field to the other fields. An empty field clears the others.
+    fn update(&mut self) {
+        let edited = &self.fields[self.edited];
+        self.error = None;
+        if edited.as_str().is_empty() {
+            self.fields.iter_mut().for_each(|field| field.clear());
+            return;
+        }
+        let input = edited.as_str().to_string();
+        let unit = self.units[self.edited];
+        let value = match edited.value() {
+            Some(value) => value,
+            None => {
+                self.error = Some(ConversionError::NotANumber(input));
+                return;
+            }
+        };
+        let minimum = (unit.from_base)(self.minimum);
+        if value < minimum {
+            self.error = Some(ConversionError::BelowMinimum {
+                input,
+                minimum,
+                unit: unit.name,
+            });
+            return;
+        }
+
+        let base = (unit.to_base)(value);
+        let values: Vec<f64> = self
+            .units
+            .iter()
+            .map(|unit| (unit.from_base)(base))
+            .collect();
+        if !values.iter().all(|value| value.is_finite()) {
+            self.error = Some(ConversionError::TooLarge);
+            return;
+        }
+        let separator = edited.separator();
+        for (index, (field, value)) in self.fields.iter_mut().zip(values).enumerate() {
+            if index != self.edited {
+                field.set_value(value, usize::from(self.precision), separator);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConversionError {
+    /// The input is not a complete number, e.g. only a sign.
+    NotANumber(String),
+    BelowMinimum {
+        input: String,
+        minimum: f64,
+        unit: &'static str,
+    },
+    /// The input or its value in another unit does not fit into an `f64`.
+    TooLarge,
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::NotANumber(input) => write!(f, "`{}` is not a number", input),
+            ConversionError::BelowMinimum {
+                input,
+                minimum,
+                unit,
+            } => write!(
+                f,
+                "`{}` is below the minimum of {} {}",
+                input, minimum, unit
+            ),
+            ConversionError::TooLarge => f.write_str("the number is too large to convert"),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
@@ -1,4 +1,5 @@
 use std::{
+    ops::{Range, RangeInclusive},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Condvar, Mutex,
@@ -7,6 +8,8 @@ use std::{
     time::Duration,
 };
 
+use eframe::egui;
+
 pub struct DispatcherTimer {
     is_running: Arc<AtomicBool>,
     sender: Sender,
@@ -105,3 +108,208 @@ impl Receiver {
         }
     }
 }
+
+/// Text of a number that only accepts edits which keep it the beginning of a number: an optional
+/// sign, digits and a decimal separator, which is either a point or a comma.
+///
+/// The `-` sign is only accepted if the range allows negative numbers. Numbers outside of the
+/// range can still be typed because they might be the beginning of a number in range, but
+/// stepping always ends in range.
+#[derive(Debug, Clone)]
+pub struct NumberText {
+    text: String,
+    range: RangeInclusive<f64>,
+    decimals: bool,
+    step: f64,
+}
+
+impl Default for NumberText {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NumberText {
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+            range: f64::NEG_INFINITY..=f64::INFINITY,
+            decimals: true,
+            step: 1.0,
+        }
+    }
+
+    /// Only accepts whole numbers.
+    pub fn integer(mut self) -> Self {
+        self.decimals = false;
+        self
+    }
+
+    pub fn range(mut self, range: RangeInclusive<f64>) -> Self {
+        self.range = range;
+        self
+    }
+
+    /// Amount the arrow keys add or subtract.
+    pub fn step(mut self, step: f64) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// The number, or `None` if the text is not a complete number, e.g. only a sign.
+    pub fn value(&self) -> Option<f64> {
+        self.text.replace(',', ".").parse().ok()
+    }
+
+    pub fn is_in_range(&self) -> bool {
+        self.value()
+            .is_some_and(|value| self.range.contains(&value))
+    }
+
+    /// Shows `value` with at most `precision` decimals, leaving out trailing zeros. A value in
+    /// range is rounded toward the inside of the range if rounding to the nearest number would
+    /// leave it, e.g. -459.67 with a minimum of -459.67 becomes -459.6 instead of -459.7.
+    pub fn set_value(&mut self, value: f64, precision: usize, separator: char) {
+        let precision = if self.decimals { precision } else { 0 };
+        let mut text = format!("{:.*}", precision, value);
+        let rounded: f64 = text.parse().unwrap_or(value);
+        if self.range.contains(&value) && !self.range.contains(&rounded) {
+            let scale = 10f64.powi(precision as i32);
+            let inside = if rounded < *self.range.start() {
+                (value * scale).ceil() / scale
+            } else {
+                (value * scale).floor() / scale
+            };
+            text = format!("{:.*}", precision, inside);
+        }
+        if text.contains('.') {
+            text.truncate(text.trim_end_matches('0').trim_end_matches('.').len());
+        }
+        if text == "-0" {
+            text.remove(0);
+        }
+        self.text = text.replace('.', &separator.to_string());
+    }
+
+    /// Decimal separator of the text, a point if it has none.
+    pub fn separator(&self) -> char {
+        separator(&self.text)
+    }
+
+    /// Adds `steps` times the step to the value, or to the closest number in range to zero if
+    /// the text is not a number, and keeps the result in range. Returns whether the text changed,
+    /// which it does not if the value is too large for an `f64`.
+    pub fn step_by(&mut self, steps: f64) -> bool {
+        let (min, max) = (*self.range.start(), *self.range.end());
+        let value = self.value().unwrap_or_else(|| 0f64.max(min).min(max));
+        let value = (value + steps * self.step).max(min).min(max);
+        if !value.is_finite() {
+            return false;
+        }
+        let precision = decimals(&self.text).max(decimals(&self.step.to_string()));
+        let old_text = std::mem::take(&mut self.text);
+        self.set_value(value, precision, separator(&old_text));
+        self.text != old_text
+    }
+
+    /// Whether the text is the beginning of a number.
+    fn is_valid(&self) -> bool {
+        let digits = match self.text.strip_prefix('-') {
+            Some(digits) if *self.range.start() < 0.0 => digits,
+            Some(_) => return false,
+            None => self.text.strip_prefix('+').unwrap_or(&self.text),
+        };
+        digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || is_separator(c))
+            && digits.matches(is_separator).count() <= usize::from(self.decimals)
+    }
+}
+
+impl AsRef<str> for NumberText {
+    fn as_ref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl From<NumberText> for String {
+    fn from(value: NumberText) -> Self {
+        value.text
+    }
+}
+
+impl egui::TextBuffer for NumberText {
+    /// Inserts every character of `text` that keeps this the beginning of a number, so that
+    /// pasting `1 234,5 €` inserts `1234,5`.
+    fn insert_text(&mut self, text: &str, ch_idx: usize) -> usize {
+        // Only ASCII characters are accepted, so character and byte indices are the same.
+        let mut index = ch_idx.min(self.text.len());
+        let mut count = 0;
+        for c in text.chars() {
+            self.text.insert(index, c);
+            if self.is_valid() {
+                index += 1;
+                count += 1;
+            } else {
+                self.text.remove(index);
+            }
+        }
+        count
+    }
+
+    fn delete_char_range(&mut self, ch_range: Range<usize>) {
+        let end = ch_range.end.min(self.text.len());
+        self.text.drain(ch_range.start.min(end)..end);
+    }
+}
+
+/// Single line text edit of a [`NumberText`], in which the arrow keys up and down step the
+/// number while it has focus.
+pub struct NumberEdit<'a> {
+    number: &'a mut NumberText,
+    text_color: Option<egui::Color32>,
+}
+
+impl<'a> NumberEdit<'a> {
+    pub fn new(number: &'a mut NumberText) -> Self {
+        Self {
+            number,
+            text_color: None,
+        }
+    }
+
+    pub fn text_color_opt(mut self, text_color: Option<egui::Color32>) -> Self {
+        self.text_color = text_color;
+        self
+    }
+}
+
+impl egui::Widget for NumberEdit<'_> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let mut response =
+            ui.add(egui::TextEdit::singleline(self.number).text_color_opt(self.text_color));
+        if response.has_focus() {
+            let input = ui.input();
+            let steps = input.num_presses(egui::Key::ArrowUp) as f64
+                - input.num_presses(egui::Key::ArrowDown) as f64;
+            if steps != 0.0 && self.number.step_by(steps) {
+                response.mark_changed();
+            }
+        }
+        response
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c == '.' || c == ','
+}
+
+fn separator(text: &str) -> char {
+    text.chars().find(|&c| is_separator(c)).unwrap_or('.')
+}
+
+/// Number of digits after the decimal separator of `text`.
+fn decimals(text: &str) -> usize {
+    text.find(is_separator)
+        .map_or(0, |separator| text.len() - separator - 1)
+}
@@ -0,0 +1,55 @@
+//! Output and exit codes of the `cells-eval` binary.
+
+use std::process::{Command, Output};
+
+const PRICES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/prices.json");
+
+fn cells_eval(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cells-eval"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn prints_the_value_of_a_cell() {
+    let output = cells_eval(&[PRICES, "B0"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "4\n");
+}
+
+#[test]
+fn fails_when_a_cell_is_an_error() {
+    let output = cells_eval(&[PRICES]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "Total,4,#DIV/0\n,1.5,\n,2.5,\n");
+    let errors = stderr(&output);
+    assert!(errors.starts_with("C0: #DIV/0"), "{}", errors);
+
+    let output = cells_eval(&[PRICES, "C0"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn rejects_invalid_arguments() {
+    let cases: &[&[&str]] = &[
+        &[],
+        &[PRICES, "B0", "C0"],
+        &[PRICES, "not a cell"],
+        &["tests/fixtures/missing.json"],
+    ];
+    for args in cases {
+        let output = cells_eval(args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(stdout(&output).is_empty(), "{:?}", args);
+        assert!(stderr(&output).starts_with("cells-eval: "), "{:?}", args);
+    }
+}
@@ -0,0 +1,128 @@
+//! Reading and writing CSV files and importing them into a sheet.
+
+use guis::cells::{
+    csv::{self, CsvError},
+    Coord, Sheet, COLUMNS, ROWS,
+};
+
+fn records(records: &[&[&str]]) -> Vec<Vec<String>> {
+    records
+        .iter()
+        .map(|record| record.iter().map(|field| field.to_string()).collect())
+        .collect()
+}
+
+#[test]
+fn writes_plain_fields_as_they_are() {
+    let output = csv::write(records(&[&["a", "1"], &["", "=A0"]]));
+    assert_eq!(output, "a,1\n,=A0\n");
+}
+
+#[test]
+fn quotes_fields_with_commas_quotes_and_line_breaks() {
+    let output = csv::write(records(&[&["a,b", "say \"hi\"", "two\nlines", "cr\r"]]));
+    assert_eq!(
+        output,
+        "\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\",\"cr\r\"\n"
+    );
+}
+
+#[test]
+fn reads_written_records_back() {
+    let written = records(&[
+        &["plain", "a,b", "say \"hi\""],
+        &["two\nlines", "", "\"quoted\""],
+        &["crlf\r\ninside", ",", "\""],
+    ]);
+    assert_eq!(csv::parse(&csv::write(written.clone())), Ok(written));
+}
+
+#[test]
+fn reads_crlf_line_breaks() {
+    assert_eq!(
+        csv::parse("a,b\r\nc,d\r\n"),
+        Ok(records(&[&["a", "b"], &["c", "d"]]))
+    );
+}
+
+#[test]
+fn reads_a_last_record_without_line_break() {
+    assert_eq!(
+        csv::parse("a,b\nc,"),
+        Ok(records(&[&["a", "b"], &["c", ""]]))
+    );
+    assert_eq!(csv::parse(""), Ok(Vec::new()));
+}
+
+#[test]
+fn reports_an_unclosed_quote_on_the_line_it_starts() {
+    assert_eq!(
+        csv::parse("a,b\nc,d\ne,\"f\ng\n"),
+        Err(CsvError {
+            line: 3,
+            message: "quoted field is not closed".to_string(),
+        })
+    );
+}
+
+#[test]
+fn reports_text_after_a_quoted_field() {
+    let error = csv::parse("a\n\"two\nlines\"x\n").unwrap_err();
+    assert_eq!(error.line, 3);
+    assert_eq!(
+        error.to_string(),
+        "line 3: unexpected `x` after quoted field"
+    );
+}
+
+#[test]
+fn imports_at_the_anchor() {
+    let mut sheet = Sheet::new();
+    sheet
+        .import_csv("1,\"a,b\"\n=A0 + 1,x\n", "B2".parse().unwrap())
+        .unwrap();
+    let input = |coord: &str| sheet.input(coord.parse().unwrap()).to_string();
+    assert_eq!(input("B2"), "1");
+    assert_eq!(input("C2"), "a,b");
+    assert_eq!(input("B3"), "=A0 + 1");
+    assert_eq!(input("C3"), "x");
+    assert_eq!(input("A0"), "");
+}
+
+#[test]
+fn drops_fields_outside_of_the_grid() {
+    let mut sheet = Sheet::new();
+    let anchor = Coord {
+        column: COLUMNS - 1,
+        row: ROWS - 2,
+    };
+    sheet.import_csv("a,b\nc,d\ne,f\n", anchor).unwrap();
+    let used: Vec<(Coord, String)> = sheet
+        .used_coords()
+        .into_iter()
+        .map(|coord| (coord, sheet.input(coord).to_string()))
+        .collect();
+    assert_eq!(
+        used,
+        vec![
+            (anchor, "a".to_string()),
+            (
+                Coord {
+                    column: COLUMNS - 1,
+                    row: ROWS - 1,
+                },
+                "c".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn leaves_the_sheet_unchanged_on_errors() {
+    let mut sheet = Sheet::new();
+    sheet.set("A0".parse().unwrap(), "kept");
+    assert!(sheet
+        .import_csv("new,\"open\n", "A0".parse().unwrap())
+        .is_err());
+    assert_eq!(sheet.input("A0".parse().unwrap()), "kept");
+}
@@ -0,0 +1,69 @@
+//! Circular references between cells and recovering from them.
+
+use guis::cells::{Coord, Sheet};
+
+fn coord(name: &str) -> Coord {
+    name.parse().unwrap()
+}
+
+fn coords(names: &[&str]) -> Vec<Coord> {
+    names.iter().map(|name| coord(name)).collect()
+}
+
+fn value(sheet: &Sheet, name: &str) -> String {
+    sheet.value(coord(name)).to_string()
+}
+
+#[test]
+fn finds_the_path_of_a_cycle() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A1"), "=B1");
+    sheet.set(coord("B1"), "=A1");
+    assert_eq!(value(&sheet, "A1"), "#CYCLE");
+    assert_eq!(value(&sheet, "B1"), "#CYCLE");
+    assert_eq!(sheet.cycle(coord("B1")), Some(coords(&["B1", "A1", "B1"])));
+    assert_eq!(sheet.cycle(coord("A1")), Some(coords(&["A1", "B1", "A1"])));
+}
+
+#[test]
+fn spreads_cycle_errors_to_dependents() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A1"), "=B1");
+    sheet.set(coord("B1"), "=A1");
+    sheet.set(coord("C1"), "=A1 + 1");
+    assert_eq!(value(&sheet, "C1"), "#CYCLE");
+    assert!(!sheet.is_in_cycle(coord("C1")));
+    assert_eq!(sheet.cycle(coord("C1")), None);
+}
+
+#[test]
+fn recovers_once_the_cycle_is_broken() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A1"), "=B1");
+    sheet.set(coord("B1"), "=A1");
+    sheet.set(coord("C1"), "=A1 + 1");
+    sheet.set(coord("B1"), "2");
+    for name in &["A1", "B1", "C1"] {
+        assert!(!sheet.is_in_cycle(coord(name)), "{}", name);
+    }
+    assert_eq!(value(&sheet, "A1"), "2");
+    assert_eq!(value(&sheet, "C1"), "3");
+
+    sheet.undo();
+    assert_eq!(value(&sheet, "C1"), "#CYCLE");
+    assert!(sheet.is_in_cycle(coord("B1")));
+}
+
+#[test]
+fn finds_cycles_through_ranges() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("E0"), "1");
+    sheet.set(coord("E2"), "2");
+    sheet.set(coord("E1"), "=SUM(E0:E2)");
+    assert_eq!(value(&sheet, "E1"), "#CYCLE");
+    assert_eq!(sheet.cycle(coord("E1")), Some(coords(&["E1", "E1"])));
+
+    sheet.set(coord("E1"), "=SUM(E0, E2)");
+    assert_eq!(value(&sheet, "E1"), "3");
+    assert!(!sheet.is_in_cycle(coord("E1")));
+}
@@ -0,0 +1,93 @@
+//! Saving and opening documents.
+
+use guis::cells::{
+    document::{Document, DocumentError, VERSION},
+    format::{Align, CellFormat, NumberFormat},
+    names::NameTarget,
+    Coord, Sheet,
+};
+
+fn coord(name: &str) -> Coord {
+    name.parse().unwrap()
+}
+
+fn sheet() -> Sheet {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "Total");
+    sheet.set(coord("B0"), "=sum(prices)");
+    sheet.set(coord("B1"), "1.5");
+    sheet.set(coord("B2"), "2.5");
+    sheet.set_format(
+        coord("B0"),
+        CellFormat {
+            align: Align::Right,
+            number: NumberFormat::Fixed { decimals: 2 },
+        },
+    );
+    sheet
+        .define_name("prices", NameTarget::Range(coord("B1"), coord("B2")))
+        .unwrap();
+    sheet
+}
+
+#[test]
+fn opens_saved_documents() {
+    let document = sheet().to_document();
+    let opened = Document::from_json(&document.to_json()).unwrap();
+    assert_eq!(opened, document);
+
+    let mut sheet = Sheet::new();
+    sheet.set(coord("C5"), "replaced");
+    sheet.load_document(opened);
+    assert_eq!(sheet.to_document(), document);
+    assert_eq!(sheet.input(coord("C5")), "");
+    assert_eq!(sheet.value(coord("B0")).to_string(), "4");
+    assert!(!sheet.can_undo());
+}
+
+#[cfg(feature = "gui")]
+#[test]
+fn saves_and_opens_files() {
+    use guis::cells::CellsModel;
+
+    let mut document = sheet().to_document();
+    document.column_widths.insert("B".to_string(), 120.0);
+    let mut model = CellsModel::default();
+    model.load_document(document.clone());
+
+    let path = std::env::temp_dir().join(format!("cells-document-{}.json", std::process::id()));
+    model.save(&path).unwrap();
+    let mut opened = CellsModel::default();
+    let result = opened.open(&path);
+    std::fs::remove_file(&path).unwrap();
+    result.unwrap();
+    assert_eq!(opened.to_document(), document);
+}
+
+#[test]
+fn rejects_newer_versions() {
+    let input = format!(r#"{{ "version": {}, "cells": "anything" }}"#, VERSION + 1);
+    match Document::from_json(&input) {
+        Err(DocumentError::UnsupportedVersion(version)) => assert_eq!(version, VERSION + 1),
+        result => panic!("expected an unsupported version, got {:?}", result),
+    }
+}
+
+#[test]
+fn rejects_corrupted_documents() {
+    let json = sheet().to_document().to_json();
+    let cases = [
+        json[..json.len() / 2].to_string(),
+        String::new(),
+        "[]".to_string(),
+        r#"{ "cells": [] }"#.to_string(),
+        r#"{ "version": 1, "cells": [{ "coord": "A", "input": "1" }] }"#.to_string(),
+        r#"{ "version": 1, "names": { "A1": "$B$1" } }"#.to_string(),
+    ];
+    for input in &cases {
+        match Document::from_json(input) {
+            Err(DocumentError::Corrupted(_)) => {}
+            result => panic!("expected {:?} to be corrupted, got {:?}", input, result),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! Evaluating formulas: coercing values, propagating errors and operator precedence.
+
+use guis::cells::{Coord, Sheet};
+
+fn coord(name: &str) -> Coord {
+    name.parse().unwrap()
+}
+
+#[test]
+fn evaluates_formulas() {
+    let cases = [
+        ("=\"x\" + 1", "#VALUE"),
+        ("=\"2\" + 1", "3"),
+        ("=Z9 + 1", "1"),
+        ("=Z9 * 2", "0"),
+        ("=A0 + 1", "#DIV/0"),
+        ("=SUM(A0, 1)", "#DIV/0"),
+        ("=SUM(A0:A1)", "#DIV/0"),
+        ("=nosuchfunction(1)", "#NAME"),
+        ("=2^3^2", "512"),
+        ("=-2^2", "-4"),
+        ("=(-2)^2", "4"),
+        ("=1 + 2 * 3", "7"),
+    ];
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "=1/0");
+    sheet.set(coord("A1"), "2");
+    for &(input, expected) in &cases {
+        sheet.set(coord("B0"), input);
+        assert_eq!(sheet.value(coord("B0")).to_string(), expected, "{}", input);
+    }
+}
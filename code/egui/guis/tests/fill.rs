@@ -0,0 +1,74 @@
+//! Filling areas of a sheet with series and formulas.
+
+use guis::cells::{Coord, Fill, Sheet};
+
+fn coord(name: &str) -> Coord {
+    name.parse().unwrap()
+}
+
+fn column(sheet: &Sheet, column: usize, rows: usize) -> Vec<String> {
+    (0..rows)
+        .map(|row| sheet.input(Coord { column, row }).to_string())
+        .collect()
+}
+
+#[test]
+fn continues_number_series() {
+    let cases: [(&[&str], &[&str]); 3] = [
+        (&["1", "2"], &["1", "2", "3", "4", "5", "6"]),
+        (&["0.1", "0.2"], &["0.1", "0.2", "0.3", "0.4", "0.5", "0.6"]),
+        (&["10", "7"], &["10", "7", "4", "1", "-2", "-5"]),
+    ];
+    for (seed, expected) in &cases {
+        let mut sheet = Sheet::new();
+        for (row, input) in seed.iter().enumerate() {
+            sheet.set(Coord { column: 0, row }, *input);
+        }
+        sheet.fill(coord("A0"), coord("A5"), Fill::Down);
+        assert_eq!(column(&sheet, 0, 6), *expected, "{:?}", seed);
+    }
+}
+
+#[test]
+fn repeats_text() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "a");
+    sheet.set(coord("A1"), "b");
+    sheet.fill(coord("A0"), coord("A4"), Fill::Down);
+    assert_eq!(column(&sheet, 0, 5), ["a", "b", "a", "b", "a"]);
+}
+
+#[test]
+fn moves_references_of_filled_formulas() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "1");
+    sheet.set(coord("A1"), "2");
+    sheet.set(coord("A2"), "3");
+    sheet.set(coord("B0"), "=A0 * $A$0");
+    sheet.fill(coord("B0"), coord("B2"), Fill::Down);
+    assert_eq!(
+        column(&sheet, 1, 3),
+        ["=A0 * $A$0", "=A1 * $A$0", "=A2 * $A$0"]
+    );
+    assert_eq!(sheet.value(coord("B2")).to_string(), "3");
+
+    // A single column is filled with the cells to its left.
+    sheet.fill(coord("C0"), coord("C2"), Fill::Right);
+    assert_eq!(sheet.input(coord("C1")), "=B1 * $A$0");
+}
+
+#[test]
+fn undoes_a_fill_as_one_step() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "1");
+    sheet.set(coord("A1"), "2");
+    sheet.fill(coord("A0"), coord("B5"), Fill::Down);
+    assert_eq!(column(&sheet, 0, 6), ["1", "2", "3", "4", "5", "6"]);
+
+    assert_eq!(sheet.undo(), Some((coord("A2"), coord("A5"))));
+    assert_eq!(column(&sheet, 0, 6), ["1", "2", "", "", "", ""]);
+    assert_eq!(sheet.input(coord("A1")), "2");
+
+    sheet.redo();
+    assert_eq!(sheet.input(coord("A5")), "6");
+}
@@ -0,0 +1,80 @@
+//! Number formats and formatting cells of a sheet.
+
+use guis::cells::{
+    format::{Align, CellFormat, NumberFormat},
+    Coord, Sheet,
+};
+
+fn coord(name: &str) -> Coord {
+    name.parse().unwrap()
+}
+
+#[test]
+fn formats_numbers() {
+    let cases = [
+        (NumberFormat::General, 1234.5, "1234.5"),
+        (NumberFormat::Fixed { decimals: 2 }, 1234.5, "1234.50"),
+        (
+            NumberFormat::Thousands { decimals: 2 },
+            1234567.891,
+            "1,234,567.89",
+        ),
+        (
+            NumberFormat::Thousands { decimals: 0 },
+            -123456.0,
+            "-123,456",
+        ),
+        (NumberFormat::Thousands { decimals: 1 }, 999.0, "999.0"),
+        (NumberFormat::Percent { decimals: 1 }, 0.256, "25.6%"),
+        (NumberFormat::Scientific { decimals: 2 }, 1234.5, "1.23E+03"),
+        (NumberFormat::Scientific { decimals: 1 }, 0.00042, "4.2E-04"),
+        (NumberFormat::Date, 44197.0, "2021-01-01"),
+        (NumberFormat::Date, 1.0, "1899-12-31"),
+        (NumberFormat::Date, 44197.75, "2021-01-01"),
+        (NumberFormat::Date, 1e12, "1000000000000"),
+    ];
+    for &(format, number, expected) in &cases {
+        assert_eq!(format.format(number), expected, "{:?}", format);
+    }
+}
+
+#[test]
+fn formats_only_numbers() {
+    let format = CellFormat {
+        number: NumberFormat::Percent { decimals: 0 },
+        ..CellFormat::default()
+    };
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "0.5");
+    sheet.set(coord("A1"), "text");
+    assert_eq!(format.display(sheet.value(coord("A0"))), "50%");
+    assert_eq!(format.display(sheet.value(coord("A1"))), "text");
+}
+
+#[test]
+fn formats_a_selection_as_one_step() {
+    let right = CellFormat {
+        align: Align::Right,
+        ..CellFormat::default()
+    };
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "1");
+    sheet.set_format(coord("A1"), right.clone());
+    let area = Coord::range(coord("A0"), coord("B1"));
+    sheet.set_formats(area.map(|coord| (coord, right.clone())));
+    for name in &["A0", "A1", "B0", "B1"] {
+        assert_eq!(sheet.format(coord(name)), &right, "{}", name);
+    }
+    assert_eq!(sheet.to_document().cells.len(), 4);
+
+    // A1 already had the format, so it is not part of the step.
+    assert_eq!(sheet.undo(), Some((coord("A0"), coord("B1"))));
+    assert_eq!(sheet.format(coord("A1")), &right);
+    assert!(sheet.format(coord("A0")).is_default());
+    assert!(sheet.format(coord("B1")).is_default());
+    assert_eq!(sheet.to_document().cells.len(), 2);
+
+    sheet.undo();
+    assert!(sheet.format(coord("A1")).is_default());
+    assert_eq!(sheet.input(coord("A0")), "1");
+}
@@ -0,0 +1,196 @@
+//! Property tests of the formula parser and the evaluation of the Cells task.
+
+use guis::cells::{
+    formula::{self, BinaryOp, CellRef, Expr, ExprKind, UnaryOp},
+    names::Names,
+    Coord, Sheet, COLUMNS, ROWS,
+};
+use proptest::prelude::*;
+
+const GRID: Coord = Coord {
+    column: COLUMNS,
+    row: ROWS,
+};
+
+/// Size of the grid formulas refer to when they are evaluated, which keeps ranges small.
+const EVALUATED: Coord = Coord { column: 4, row: 4 };
+
+fn coord(size: Coord) -> impl Strategy<Value = Coord> {
+    (0..size.column, 0..size.row).prop_map(|(column, row)| Coord { column, row })
+}
+
+fn cell_ref(size: Coord) -> impl Strategy<Value = CellRef> {
+    (coord(size), any::<bool>(), any::<bool>()).prop_map(
+        |(coord, absolute_column, absolute_row)| CellRef {
+            coord,
+            absolute_column,
+            absolute_row,
+        },
+    )
+}
+
+fn name() -> impl Strategy<Value = String> {
+    "[a-z_][a-z0-9_]{0,8}".prop_filter("names must not look like references", |name| {
+        Names::is_valid(name)
+    })
+}
+
+fn unary_op() -> impl Strategy<Value = UnaryOp> {
+    prop_oneof![Just(UnaryOp::Plus), Just(UnaryOp::Neg)]
+}
+
+fn binary_op() -> impl Strategy<Value = BinaryOp> {
+    prop_oneof![
+        Just(BinaryOp::Add),
+        Just(BinaryOp::Sub),
+        Just(BinaryOp::Mul),
+        Just(BinaryOp::Div),
+        Just(BinaryOp::Pow),
+        Just(BinaryOp::Concat),
+        Just(BinaryOp::Eq),
+        Just(BinaryOp::Ne),
+        Just(BinaryOp::Lt),
+        Just(BinaryOp::Le),
+        Just(BinaryOp::Gt),
+        Just(BinaryOp::Ge),
+    ]
+}
+
+/// Expressions as the parser produces them for valid formulas, without spans, referring to
+/// cells within `size`.
+fn expr(size: Coord) -> impl Strategy<Value = Expr> {
+    let leaf = prop_oneof![
+        (0u32..1000).prop_map(|number| ExprKind::Number(f64::from(number))),
+        (0.0..1e12f64).prop_map(ExprKind::Number),
+        (0.0..=f64::MAX).prop_map(ExprKind::Number),
+        (1u32..10, -330i32..=308).prop_map(|(mantissa, exponent)| {
+            ExprKind::Number(format!("{}e{}", mantissa, exponent).parse().unwrap())
+        }),
+        ".*".prop_map(ExprKind::Text),
+        any::<bool>().prop_map(ExprKind::Boolean),
+        cell_ref(size).prop_map(ExprKind::Ref),
+        (cell_ref(size), cell_ref(size)).prop_map(|(start, end)| ExprKind::Range(start, end)),
+        name().prop_map(ExprKind::Name),
+        Just(ExprKind::InvalidRef),
+    ]
+    .prop_map(spanless);
+    leaf.prop_recursive(6, 64, 4, |inner| {
+        prop_oneof![
+            (unary_op(), inner.clone())
+                .prop_map(|(op, operand)| ExprKind::Unary(op, Box::new(operand))),
+            (binary_op(), inner.clone(), inner.clone())
+                .prop_map(|(op, lhs, rhs)| ExprKind::Binary(op, Box::new(lhs), Box::new(rhs))),
+            ("[a-z][a-z0-9]{0,6}", prop::collection::vec(inner, 0..4))
+                .prop_map(|(name, args)| ExprKind::Call(name, args)),
+        ]
+        .prop_map(spanless)
+    })
+}
+
+/// Inputs that look like formulas, to get past the first token more often than random text.
+fn formula_input(size: Coord) -> impl Strategy<Value = String> {
+    prop_oneof![
+        any::<String>(),
+        "=[A-Da-d0-3_$#:(),.+*/^&=<>\" -]{0,40}",
+        expr(size).prop_map(|expr| format!("={}", expr)),
+    ]
+}
+
+fn spanless(kind: ExprKind) -> Expr {
+    Expr { kind, span: 0..0 }
+}
+
+/// The expression with all spans removed, which differ between equal expressions written
+/// differently.
+fn without_spans(expr: Expr) -> Expr {
+    let strip = |expr: Box<Expr>| Box::new(without_spans(*expr));
+    spanless(match expr.kind {
+        ExprKind::Unary(op, operand) => ExprKind::Unary(op, strip(operand)),
+        ExprKind::Binary(op, lhs, rhs) => ExprKind::Binary(op, strip(lhs), strip(rhs)),
+        ExprKind::Call(name, args) => {
+            ExprKind::Call(name, args.into_iter().map(without_spans).collect())
+        }
+        kind => kind,
+    })
+}
+
+proptest! {
+    #[test]
+    fn parsing_never_panics(input in formula_input(GRID)) {
+        let formula = formula::parse(&input);
+        for error in &formula.errors {
+            prop_assert!(error.span.start <= error.span.end && error.span.end <= input.len());
+            prop_assert!(input.is_char_boundary(error.span.start));
+            prop_assert!(input.is_char_boundary(error.span.end));
+        }
+    }
+
+    #[test]
+    fn printing_and_parsing_is_the_identity(expr in expr(GRID)) {
+        let input = format!("={}", expr);
+        let formula = formula::parse(&input);
+        prop_assert!(formula.is_valid(), "{} has errors: {:?}", input, formula.errors);
+        prop_assert_eq!(without_spans(formula.expr), expr);
+    }
+
+    #[test]
+    fn adjusting_references_never_panics(
+        input in formula_input(GRID),
+        columns in -1000isize..1000,
+        rows in -1000isize..1000,
+    ) {
+        let adjusted = formula::adjust_references(&input, |reference| {
+            reference.offset(columns, rows)
+        });
+        if (columns, rows) == (0, 0) {
+            prop_assert_eq!(adjusted, input);
+        }
+    }
+
+    #[test]
+    fn evaluating_never_panics(
+        inputs in prop::collection::vec((coord(EVALUATED), formula_input(EVALUATED)), 0..16),
+    ) {
+        let mut sheet = Sheet::new();
+        for (coord, input) in inputs {
+            sheet.set(coord, input.clone());
+            prop_assert_eq!(sheet.input(coord), input.as_str());
+        }
+        for coord in sheet.used_coords() {
+            sheet.value(coord);
+        }
+    }
+}
+
+#[test]
+fn references_outside_of_the_grid_are_unknown() {
+    for &(input, reference) in &[("=A10000", "A10000"), ("=1 + ZZZ1", "ZZZ1")] {
+        let formula = formula::parse(input);
+        assert_eq!(formula.errors.len(), 1, "{}", input);
+        let error = &formula.errors[0];
+        assert_eq!(error.message, format!("unknown reference `{}`", reference));
+        assert_eq!(&input[error.span.clone()], reference);
+    }
+}
+
+#[test]
+fn names_cannot_look_like_references() {
+    for name in &["A1", "A10000", "ZZZ1", "abc123"] {
+        assert!(!Names::is_valid(name), "{}", name);
+    }
+    for name in &["tax", "a1b", "_1", "A", "q1_rate"] {
+        assert!(Names::is_valid(name), "{}", name);
+    }
+}
+
+#[test]
+fn numbers_too_large_for_an_f64_are_errors() {
+    for &(input, number) in &[("=1e999", "1e999"), ("=2 * 18E308 + 1", "18E308")] {
+        let formula = formula::parse(input);
+        assert_eq!(formula.errors.len(), 1, "{}", input);
+        let error = &formula.errors[0];
+        assert_eq!(error.message, format!("number `{}` is too large", number));
+        assert_eq!(&input[error.span.clone()], number);
+    }
+    assert!(formula::parse("=1e308").is_valid());
+}
@@ -0,0 +1,57 @@
+//! Undoing and redoing changes of a sheet.
+
+use guis::cells::{Coord, Fill, Sheet};
+
+fn coord(name: &str) -> Coord {
+    name.parse().unwrap()
+}
+
+fn grid(sheet: &Sheet) -> Vec<Vec<String>> {
+    sheet.inputs(coord("A0"), coord("B3"))
+}
+
+#[test]
+fn undoes_and_redoes_changes_of_many_cells_as_one_step() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "1");
+    sheet.set(coord("A1"), "2");
+    let mut states = vec![grid(&sheet)];
+
+    let copied = sheet.inputs(coord("A0"), coord("A1"));
+    sheet.paste(&copied, coord("A0"), coord("B0"));
+    states.push(grid(&sheet));
+    sheet.fill(coord("A0"), coord("B3"), Fill::Down);
+    states.push(grid(&sheet));
+    sheet.clear(coord("A1"), coord("B2"));
+    states.push(grid(&sheet));
+    assert_eq!(sheet.used_coords().len(), 4);
+
+    for state in states.iter().rev().skip(1) {
+        sheet.undo();
+        assert_eq!(&grid(&sheet), state);
+    }
+    for state in states.iter().skip(1) {
+        sheet.redo();
+        assert_eq!(&grid(&sheet), state);
+    }
+    assert!(!sheet.can_redo());
+}
+
+#[test]
+fn clears_redo_with_a_new_edit() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "1");
+    sheet.set(coord("A0"), "2");
+    sheet.undo();
+    assert!(sheet.can_redo());
+
+    sheet.set(coord("B0"), "3");
+    assert!(!sheet.can_redo());
+    assert_eq!(sheet.redo(), None);
+    assert_eq!(sheet.input(coord("A0")), "1");
+
+    sheet.undo();
+    sheet.undo();
+    assert_eq!(sheet.input(coord("A0")), "");
+    assert!(!sheet.can_undo());
+}
@@ -0,0 +1,191 @@
+//! Editing of the numeric text buffer shared by tasks with number inputs.
+
+use eframe::egui::TextBuffer;
+use guis::NumberText;
+
+fn number(text: &str) -> NumberText {
+    let mut number = NumberText::new();
+    number.replace(text);
+    number
+}
+
+#[test]
+fn accepts_only_the_beginning_of_a_number() {
+    assert_eq!(number("-12.5").as_str(), "-12.5");
+    assert_eq!(number("+7").as_str(), "+7");
+    assert_eq!(number("36,6").as_str(), "36,6");
+    assert_eq!(number("1 234,5 €").as_str(), "1234,5");
+    assert_eq!(number("1-2+3").as_str(), "123");
+    assert_eq!(number("1.2.3").as_str(), "1.23");
+    assert_eq!(number("1.2,3").as_str(), "1.23");
+    assert_eq!(number("abc").as_str(), "");
+}
+
+#[test]
+fn returns_the_number_of_inserted_characters() {
+    let mut number = number("12");
+    assert_eq!(number.insert_text("3a4", 2), 2);
+    assert_eq!(number.as_str(), "1234");
+    assert_eq!(number.insert_text("x", 1), 0);
+    assert_eq!(number.as_str(), "1234");
+}
+
+#[test]
+fn keeps_the_sign_when_inserting_before_it() {
+    let mut number = number("-5");
+    assert_eq!(number.insert_text("3", 0), 0);
+    assert_eq!(number.as_str(), "-5");
+    assert_eq!(number.insert_text("-", 0), 0);
+    assert_eq!(number.as_str(), "-5");
+    assert_eq!(number.insert_text("3", 1), 1);
+    assert_eq!(number.as_str(), "-35");
+}
+
+#[test]
+fn inserts_a_sign_only_at_the_beginning() {
+    let mut number = number("5");
+    assert_eq!(number.insert_text("-", 1), 0);
+    assert_eq!(number.insert_text("-", 0), 1);
+    assert_eq!(number.as_str(), "-5");
+}
+
+#[test]
+fn inserts_a_separator_only_once() {
+    let mut number = number("12");
+    assert_eq!(number.insert_text(",", 1), 1);
+    assert_eq!(number.insert_text(".", 3), 0);
+    assert_eq!(number.as_str(), "1,2");
+    number.delete_char_range(1..2);
+    assert_eq!(number.insert_text(".", 2), 1);
+    assert_eq!(number.as_str(), "12.");
+}
+
+#[test]
+fn clamps_indices_to_the_text() {
+    let mut number = number("12");
+    assert_eq!(number.insert_text("3", 10), 1);
+    assert_eq!(number.as_str(), "123");
+    number.delete_char_range(2..10);
+    assert_eq!(number.as_str(), "12");
+    number.delete_char_range(5..10);
+    assert_eq!(number.as_str(), "12");
+}
+
+#[test]
+fn rejects_a_minus_sign_if_the_range_is_not_negative() {
+    let mut number = NumberText::new().range(0.0..=100.0);
+    number.replace("-5");
+    assert_eq!(number.as_str(), "5");
+    number.replace("+5");
+    assert_eq!(number.as_str(), "+5");
+}
+
+#[test]
+fn rejects_a_separator_for_integers() {
+    let mut number = NumberText::new().integer();
+    number.replace("12.5");
+    assert_eq!(number.as_str(), "125");
+}
+
+#[test]
+fn parses_complete_numbers() {
+    assert_eq!(number("-12.5").value(), Some(-12.5));
+    assert_eq!(number("36,6").value(), Some(36.6));
+    assert_eq!(number("5.").value(), Some(5.0));
+    assert_eq!(number(",5").value(), Some(0.5));
+    assert_eq!(number("-").value(), None);
+    assert_eq!(number(".").value(), None);
+    assert_eq!(number("").value(), None);
+}
+
+#[test]
+fn checks_the_range() {
+    let mut number = NumberText::new().range(10.0..=20.0);
+    number.replace("1");
+    assert!(!number.is_in_range());
+    number.replace("15");
+    assert!(number.is_in_range());
+    number.replace("");
+    assert!(!number.is_in_range());
+}
+
+#[test]
+fn shows_values_with_at_most_the_precision() {
+    let mut number = NumberText::new();
+    number.set_value(97.88, 1, '.');
+    assert_eq!(number.as_str(), "97.9");
+    number.set_value(32.0, 2, '.');
+    assert_eq!(number.as_str(), "32");
+    number.set_value(2.5, 3, ',');
+    assert_eq!(number.as_str(), "2,5");
+    number.set_value(-0.01, 1, '.');
+    assert_eq!(number.as_str(), "0");
+
+    let mut integer = NumberText::new().integer();
+    integer.set_value(2.6, 2, '.');
+    assert_eq!(integer.as_str(), "3");
+}
+
+#[test]
+fn rounds_values_in_range_toward_the_range() {
+    let mut number = NumberText::new().range(-459.67..=10.04);
+    number.set_value(-459.67, 1, '.');
+    assert_eq!(number.as_str(), "-459.6");
+    number.set_value(-459.67, 0, '.');
+    assert_eq!(number.as_str(), "-459");
+    number.set_value(10.04, 1, '.');
+    assert_eq!(number.as_str(), "10");
+    number.set_value(-500.0, 1, '.');
+    assert_eq!(number.as_str(), "-500");
+}
+
+#[test]
+fn steps_within_the_range() {
+    let mut number = NumberText::new().range(-1.0..=2.0);
+    assert!(number.step_by(1.0));
+    assert_eq!(number.as_str(), "1");
+    assert!(number.step_by(5.0));
+    assert_eq!(number.as_str(), "2");
+    assert!(!number.step_by(1.0));
+    assert!(number.step_by(-10.0));
+    assert_eq!(number.as_str(), "-1");
+}
+
+#[test]
+fn steps_from_the_range_if_the_text_is_not_a_number() {
+    let mut number = NumberText::new().range(10.0..=20.0);
+    number.replace("+");
+    assert!(number.step_by(1.0));
+    assert_eq!(number.as_str(), "11");
+}
+
+#[test]
+fn steps_with_the_decimals_of_the_text_and_the_step() {
+    let mut number = NumberText::new().step(0.1);
+    number.replace("1");
+    assert!(number.step_by(1.0));
+    assert_eq!(number.as_str(), "1.1");
+
+    let mut number = NumberText::new();
+    number.replace("36,60");
+    assert!(number.step_by(-1.0));
+    assert_eq!(number.as_str(), "35,6");
+}
+
+#[test]
+fn does_not_step_values_too_large_for_an_f64() {
+    let digits = "9".repeat(400);
+    let mut number = number(&digits);
+    assert_eq!(number.value(), Some(f64::INFINITY));
+    assert!(!number.step_by(1.0));
+    assert_eq!(number.as_str(), digits);
+}
+
+#[test]
+fn converts_into_its_text() {
+    let mut number = number("42");
+    assert_eq!(number.clone().take(), "42");
+    assert_eq!(String::from(number.clone()), "42");
+    number.clear();
+    assert_eq!(number.as_str(), "");
+}
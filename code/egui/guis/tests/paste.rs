@@ -0,0 +1,52 @@
+//! Copying and pasting inputs between cells of a sheet.
+
+use guis::cells::{Coord, Sheet};
+
+fn coord(name: &str) -> Coord {
+    name.parse().unwrap()
+}
+
+fn rows(rows: &[&[&str]]) -> Vec<Vec<String>> {
+    rows.iter()
+        .map(|row| row.iter().map(|input| input.to_string()).collect())
+        .collect()
+}
+
+#[test]
+fn moves_relative_references_with_the_cells() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A1"), "1");
+    sheet.set(coord("B2"), "=A1+$A$1+A$1+$A1");
+    let copied = sheet.inputs(coord("B2"), coord("B2"));
+    let pasted = sheet.paste(&copied, coord("B2"), coord("C3"));
+    assert_eq!(pasted, Some((coord("C3"), coord("C3"))));
+    assert_eq!(sheet.input(coord("C3")), "=B2+$A$1+B$1+$A2");
+    sheet.paste(&copied, coord("B2"), coord("C4"));
+    assert_eq!(sheet.input(coord("C4")), "=B3+$A$1+B$1+$A3");
+    assert_eq!(sheet.input(coord("B2")), "=A1+$A$1+A$1+$A1");
+}
+
+#[test]
+fn turns_references_moved_off_the_grid_into_ref_errors() {
+    let mut sheet = Sheet::new();
+    let pasted = sheet.paste(&rows(&[&["=A1 + B0"]]), coord("B2"), coord("A2"));
+    assert_eq!(pasted, Some((coord("A2"), coord("A2"))));
+    assert_eq!(sheet.input(coord("A2")), "=#REF + A0");
+    assert_eq!(sheet.value(coord("A2")).to_string(), "#REF");
+
+    sheet.paste(&rows(&[&["=A1"]]), coord("B2"), coord("B0"));
+    assert_eq!(sheet.input(coord("B0")), "=#REF");
+}
+
+#[test]
+fn pastes_text_and_drops_inputs_outside_of_the_grid() {
+    let mut sheet = Sheet::new();
+    let corner = Coord {
+        column: guis::cells::COLUMNS - 1,
+        row: 0,
+    };
+    let pasted = sheet.paste(&rows(&[&["a", "b"], &["c", "d"]]), coord("A0"), corner);
+    assert_eq!(pasted, Some((corner, corner.offset(0, 1).unwrap())));
+    assert_eq!(sheet.input(corner), "a");
+    assert_eq!(sheet.used_coords().len(), 2);
+}
@@ -0,0 +1,93 @@
+//! Sorting the rows of an area of a sheet.
+
+use guis::cells::{
+    format::{Align, CellFormat},
+    sort::SortKey,
+    Coord, Sheet,
+};
+
+fn coord(name: &str) -> Coord {
+    name.parse().unwrap()
+}
+
+fn column(sheet: &Sheet, column: usize, rows: usize) -> Vec<String> {
+    (0..rows)
+        .map(|row| sheet.input(Coord { column, row }).to_string())
+        .collect()
+}
+
+#[test]
+fn moves_formulas_with_their_rows() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "name");
+    sheet.set(coord("A1"), "b");
+    sheet.set(coord("A2"), "a");
+    sheet.set(coord("B1"), "=A1");
+    sheet.set(coord("B2"), "=A2");
+    let keys = [SortKey {
+        column: 0,
+        descending: false,
+    }];
+    sheet.sort(coord("A0"), coord("B2"), &keys, true);
+    assert_eq!(column(&sheet, 0, 3), ["name", "a", "b"]);
+    assert_eq!(column(&sheet, 1, 3), ["", "=A1", "=A2"]);
+    assert_eq!(sheet.value(coord("B1")).to_string(), "a");
+}
+
+#[test]
+fn undoes_the_order_of_formats_with_the_inputs() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "2");
+    sheet.set(coord("A1"), "1");
+    let right = CellFormat {
+        align: Align::Right,
+        ..CellFormat::default()
+    };
+    sheet.set_format(coord("A0"), right.clone());
+    let keys = [SortKey {
+        column: 0,
+        descending: false,
+    }];
+
+    sheet.sort(coord("A0"), coord("A1"), &keys, false);
+    assert_eq!(column(&sheet, 0, 2), ["1", "2"]);
+    assert_eq!(sheet.format(coord("A1")), &right);
+    assert!(sheet.format(coord("A0")).is_default());
+
+    sheet.undo();
+    assert_eq!(column(&sheet, 0, 2), ["2", "1"]);
+    assert_eq!(sheet.format(coord("A0")), &right);
+    assert!(sheet.format(coord("A1")).is_default());
+
+    sheet.redo();
+    assert_eq!(sheet.format(coord("A1")), &right);
+}
+
+#[test]
+fn keeps_formats_set_after_sorting_when_undoing_them() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "2");
+    sheet.set(coord("A1"), "1");
+    let format = |align| CellFormat {
+        align,
+        ..CellFormat::default()
+    };
+    sheet.set_format(coord("A0"), format(Align::Right));
+    let keys = [SortKey {
+        column: 0,
+        descending: false,
+    }];
+    sheet.sort(coord("A0"), coord("A1"), &keys, false);
+    sheet.set_format(coord("A1"), format(Align::Center));
+    sheet.set_format(coord("A0"), format(Align::Left));
+
+    sheet.undo();
+    assert_eq!(sheet.format(coord("A0")), &CellFormat::default());
+    assert_eq!(sheet.format(coord("A1")), &format(Align::Center));
+    sheet.undo();
+    assert_eq!(sheet.format(coord("A1")), &format(Align::Right));
+    sheet.undo();
+    assert_eq!(column(&sheet, 0, 2), ["2", "1"]);
+    assert_eq!(sheet.format(coord("A0")), &format(Align::Right));
+    assert!(sheet.format(coord("A1")).is_default());
+}
@@ -0,0 +1,215 @@
+//! Inserting and deleting rows and columns of a sheet, and undoing it.
+
+use guis::cells::{
+    filter::AutoFilter,
+    format::{Align, CellFormat},
+    names::NameTarget,
+    structure::{Axis, StructureChange},
+    Coord, Sheet,
+};
+
+fn coord(name: &str) -> Coord {
+    name.parse().unwrap()
+}
+
+fn insert_rows(at: usize, count: usize) -> StructureChange {
+    StructureChange::Insert {
+        axis: Axis::Rows,
+        at,
+        count,
+    }
+}
+
+fn delete_rows(at: usize, count: usize) -> StructureChange {
+    StructureChange::Delete {
+        axis: Axis::Rows,
+        at,
+        count,
+    }
+}
+
+/// Inputs of all used cells with their names, row by row.
+fn inputs(sheet: &Sheet) -> Vec<(String, String)> {
+    sheet
+        .used_coords()
+        .into_iter()
+        .map(|coord| (coord.to_string(), sheet.input(coord).to_string()))
+        .collect()
+}
+
+fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|&(coord, input)| (coord.to_string(), input.to_string()))
+        .collect()
+}
+
+#[test]
+fn moves_cells_and_references_behind_inserted_columns() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "1");
+    sheet.set(coord("B0"), "2");
+    sheet.set(coord("C0"), "=A0 + $B$0");
+    sheet.change_structure(StructureChange::Insert {
+        axis: Axis::Columns,
+        at: 1,
+        count: 1,
+    });
+    assert_eq!(
+        inputs(&sheet),
+        pairs(&[("A0", "1"), ("C0", "2"), ("D0", "=A0 + $C$0")])
+    );
+    assert_eq!(sheet.value(coord("D0")).to_string(), "3");
+}
+
+#[test]
+fn grows_ranges_with_rows_inserted_inside_of_them() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "1");
+    sheet.set(coord("A1"), "2");
+    sheet.set(coord("B0"), "=sum(A0:A1)");
+    sheet.change_structure(insert_rows(1, 2));
+    assert_eq!(sheet.input(coord("B0")), "=sum(A0:A3)");
+    sheet.set(coord("A2"), "4");
+    assert_eq!(sheet.value(coord("B0")).to_string(), "7");
+}
+
+#[test]
+fn shrinks_ranges_with_deleted_rows() {
+    let mut sheet = Sheet::new();
+    for (row, input) in ["1", "2", "3", "4"].iter().enumerate() {
+        sheet.set(Coord { column: 0, row }, *input);
+    }
+    sheet.set(coord("B0"), "=sum(A0:A3)");
+    sheet.change_structure(delete_rows(1, 2));
+    assert_eq!(sheet.input(coord("B0")), "=sum(A0:A1)");
+    assert_eq!(sheet.value(coord("B0")).to_string(), "5");
+}
+
+#[test]
+fn turns_references_to_deleted_cells_into_ref_errors() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A1"), "2");
+    sheet.set(coord("A3"), "=A1 + 1");
+    sheet.set(coord("B0"), "=sum(A1:A1)");
+    sheet.change_structure(delete_rows(1, 1));
+    assert_eq!(sheet.input(coord("A2")), "=#REF + 1");
+    assert_eq!(sheet.value(coord("A2")).to_string(), "#REF");
+    assert_eq!(sheet.input(coord("B0")), "=sum(#REF)");
+}
+
+#[test]
+fn removes_names_of_deleted_cells() {
+    let mut sheet = Sheet::new();
+    sheet
+        .define_name("gone", NameTarget::Cell(coord("A1")))
+        .unwrap();
+    sheet.set(coord("B0"), "=gone");
+    sheet.change_structure(delete_rows(1, 1));
+    assert_eq!(sheet.names().count(), 0);
+    assert_eq!(sheet.value(coord("B0")).to_string(), "#NAME");
+}
+
+#[test]
+fn undoes_names_formats_and_the_filter_with_the_inputs() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "5");
+    sheet
+        .define_name("tax", NameTarget::Cell(coord("A0")))
+        .unwrap();
+    sheet.set(coord("B0"), "=tax");
+    let right = CellFormat {
+        align: Align::Right,
+        ..CellFormat::default()
+    };
+    sheet.set_format(coord("A0"), right.clone());
+    let filter = AutoFilter::new(coord("A0"), coord("B5"));
+    sheet.set_filter(Some(filter.clone()));
+
+    sheet.change_structure(insert_rows(0, 1));
+    assert_eq!(sheet.input(coord("B1")), "=tax");
+    assert_eq!(sheet.value(coord("B1")).to_string(), "5");
+    assert_eq!(sheet.format(coord("A1")), &right);
+    assert_eq!(sheet.filter().unwrap().start(), coord("A1"));
+
+    assert_eq!(sheet.undo(), Some((coord("A0"), coord("B1"))));
+    let names: Vec<(&str, NameTarget)> = sheet.names().collect();
+    assert_eq!(names, vec![("tax", NameTarget::Cell(coord("A0")))]);
+    assert_eq!(inputs(&sheet), pairs(&[("A0", "5"), ("B0", "=tax")]));
+    assert_eq!(sheet.value(coord("B0")).to_string(), "5");
+    assert_eq!(sheet.format(coord("A0")), &right);
+    assert!(sheet.format(coord("A1")).is_default());
+    assert_eq!(sheet.filter(), Some(&filter));
+
+    sheet.redo();
+    assert_eq!(sheet.value(coord("B1")).to_string(), "5");
+    assert_eq!(sheet.format(coord("A1")), &right);
+    assert!(sheet.format(coord("A0")).is_default());
+    assert_eq!(sheet.filter().unwrap().start(), coord("A1"));
+}
+
+#[test]
+fn undoes_deleting_rows() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "1");
+    sheet.set(coord("A1"), "2");
+    sheet.set(coord("A2"), "=A1 * 10");
+    sheet
+        .define_name("second", NameTarget::Cell(coord("A1")))
+        .unwrap();
+    sheet.change_structure(delete_rows(1, 1));
+    assert_eq!(sheet.names().count(), 0);
+
+    sheet.undo();
+    assert_eq!(
+        inputs(&sheet),
+        pairs(&[("A0", "1"), ("A1", "2"), ("A2", "=A1 * 10")])
+    );
+    assert_eq!(sheet.value(coord("A2")).to_string(), "20");
+    assert_eq!(
+        sheet.names().collect::<Vec<_>>(),
+        vec![("second", NameTarget::Cell(coord("A1")))]
+    );
+}
+
+#[test]
+fn keeps_names_and_filters_changed_after_a_structure_change() {
+    let mut sheet = Sheet::new();
+    sheet.set(coord("A0"), "5");
+    sheet
+        .define_name("early", NameTarget::Cell(coord("A0")))
+        .unwrap();
+    sheet.change_structure(insert_rows(0, 1));
+    sheet
+        .define_name("late", NameTarget::Cell(coord("A1")))
+        .unwrap();
+    let filter = AutoFilter::new(coord("A1"), coord("A5"));
+    sheet.set_filter(Some(filter.clone()));
+    sheet.set(coord("B0"), "=late * 2");
+
+    sheet.undo();
+    sheet.undo();
+    let names: Vec<(&str, NameTarget)> = sheet.names().collect();
+    assert_eq!(
+        names,
+        vec![
+            ("early", NameTarget::Cell(coord("A1"))),
+            ("late", NameTarget::Cell(coord("A1"))),
+        ]
+    );
+    assert_eq!(sheet.filter(), None);
+
+    sheet.redo();
+    sheet.redo();
+    assert_eq!(sheet.filter(), Some(&filter));
+    assert_eq!(sheet.value(coord("B0")).to_string(), "10");
+
+    // Undoing the name and the structure change restores the name as it was before both.
+    sheet.undo();
+    sheet.undo();
+    sheet.undo();
+    sheet.undo();
+    let names: Vec<(&str, NameTarget)> = sheet.names().collect();
+    assert_eq!(names, vec![("early", NameTarget::Cell(coord("A0")))]);
+    assert_eq!(sheet.input(coord("A0")), "5");
+}
@@ -0,0 +1,77 @@
+//! Converting between the linked fields of a unit converter.
+
+use guis::unit_converter::{ConversionError, Unit, UnitConverter, TEMPERATURE};
+
+/// Length units with metres as base unit.
+const LENGTH: &[Unit] = &[
+    Unit {
+        name: "Metres",
+        to_base: |metres| metres,
+        from_base: |metres| metres,
+    },
+    Unit {
+        name: "Kilometres",
+        to_base: |kilometres| kilometres * 1000.0,
+        from_base: |metres| metres / 1000.0,
+    },
+    Unit {
+        name: "Millimetres",
+        to_base: |millimetres| millimetres / 1000.0,
+        from_base: |metres| metres * 1000.0,
+    },
+];
+
+#[test]
+fn rejects_temperatures_below_absolute_zero() {
+    let mut converter = UnitConverter::new(TEMPERATURE).minimum(0.0);
+    converter.edit(0, "-273.16");
+    assert!(matches!(
+        converter.error(),
+        Some(ConversionError::BelowMinimum {
+            unit: "Celsius",
+            ..
+        })
+    ));
+    assert_eq!(converter.texts(), ["-273.16", "", "", ""]);
+}
+
+#[test]
+fn accepts_absolute_zero() {
+    let mut converter = UnitConverter::new(TEMPERATURE).minimum(0.0);
+    converter.edit(1, "-459.67");
+    assert_eq!(converter.error(), None);
+    assert_eq!(converter.texts()[1..], ["-459.67", "0", "0"]);
+}
+
+#[test]
+fn rounds_converted_values_toward_the_minimum() {
+    let mut converter = UnitConverter::new(TEMPERATURE).minimum(0.0);
+    converter.edit(2, "0");
+    assert_eq!(converter.texts(), ["-273.1", "-459.6", "0", "0"]);
+
+    // The rounded values can be edited without falling below the minimum.
+    let fahrenheit = converter.texts()[1].to_string();
+    converter.edit(1, &fahrenheit);
+    assert_eq!(converter.error(), None);
+    converter.edit(0, "-273.1");
+    assert_eq!(converter.error(), None);
+}
+
+#[test]
+fn rejects_values_too_large_for_another_unit() {
+    let mut converter = UnitConverter::new(LENGTH);
+    converter.edit(0, "1");
+    let converted: Vec<String> = converter
+        .texts()
+        .iter()
+        .map(|text| text.to_string())
+        .collect();
+
+    // 10^306 kilometres are 10^309 metres, which is more than `f64::MAX`.
+    let kilometres = format!("1{}", "0".repeat(306));
+    converter.edit(1, &kilometres);
+    assert_eq!(converter.error(), Some(&ConversionError::TooLarge));
+    assert!(converter.texts().iter().all(|text| !text.contains("inf")));
+    assert_eq!(converter.texts()[0], converted[0]);
+    assert_eq!(converter.texts()[2..], converted[2..]);
+}